@@ -13,32 +13,220 @@ pub struct EtlConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum DataSourceConfig {
-    Api {
-        url: String,
-        method: Option<String>,
-        headers: Option<HashMap<String, String>>,
-        auth: Option<AuthConfig>,
-        retry: Option<RetryConfig>,
-    },
+    Api(ApiSourceConfig),
+    Graphql(GraphQlSourceConfig),
     LocalFile {
         path: String,
         format: FileFormat,
         encoding: Option<String>,
+        /// JSON 檔案中記錄所在的節點，語法同 `ApiSourceConfig::records_path`
+        records_path: Option<String>,
+        envelope_fields: Option<HashMap<String, String>>,
     },
     Database {
+        /// SQLite 為檔案路徑（可加 `sqlite://` 前綴）或 `:memory:`；SurrealDB 為 `mem://` 或 `surrealkv://<path>`
         connection_string: String,
         query: String,
         driver: DatabaseDriver,
+        /// 查詢參數：陣列對應 `?` / `?1`，物件對應 `:name`（SurrealDB 為 `$name`）；字串值可使用 `${VAR}`
+        params: Option<serde_json::Value>,
+        /// SurrealDB 的 namespace 與 database（預設皆為 `etl`）
+        namespace: Option<String>,
+        database: Option<String>,
     },
     S3 {
         bucket: String,
+        /// 物件 key；以 `/` 結尾時讀取該前綴下所有物件，可使用 `*`、`**`、`?` 萬用字元
         key: String,
         region: String,
+        /// 未設定時讀取 `AWS_ACCESS_KEY_ID`、`AWS_SECRET_ACCESS_KEY` 環境變數
         credentials: Option<AwsCredentials>,
+        /// S3 相容服務（例如 MinIO）的網址，例如 `http://localhost:9000`
+        endpoint: Option<String>,
+        /// 以 `endpoint/bucket/key` 定址（設定 `endpoint` 時預設 true）
+        path_style: Option<bool>,
+        /// 未設定時依副檔名判斷，無法判斷時依內容偵測
+        format: Option<FileFormat>,
     },
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiSourceConfig {
+    pub url: String,
+    pub method: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+    /// 附加到 URL 的查詢參數，值可使用 `${VAR}` 與日期運算式
+    pub query_params: Option<HashMap<String, String>>,
+    pub body: Option<ApiRequestBody>,
+    pub auth: Option<AuthConfig>,
+    pub retry: Option<RetryConfig>,
+    pub pagination: Option<PaginationConfig>,
+    /// JSONPath 風格的記錄節點路徑，例如 `$.data.items` 或 `$.pages[*].rows`
+    pub records_path: Option<String>,
+    /// 複製到每筆記錄的外層欄位：目標欄位名稱 -> 路徑（例如 `"generated_at": "$.meta.generated_at"`）
+    pub envelope_fields: Option<HashMap<String, String>>,
+    /// 速率限制，分頁與並行請求共用同一組配額
+    pub rate_limit: Option<RateLimitConfig>,
+    /// 逾時、代理伺服器與 TLS 設定
+    pub http: Option<HttpClientConfig>,
+    /// 以 `ETag` / `Last-Modified` 發送條件式請求並快取回應
+    pub cache: Option<HttpCacheConfig>,
+    /// 回應格式（預設 json）；`auto` 依 Content-Type、檔名與內容判斷
+    pub response_format: Option<ResponseFormat>,
+}
+
+/// API 回應的格式，json 以外的格式會先串流寫入暫存目錄再解析
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseFormat {
+    #[default]
+    Json,
+    Auto,
+    Ndjson,
+    Csv,
+    Xml,
+    Zip,
+    /// gzip 壓縮的 JSON、NDJSON、CSV 或 XML
+    Gzip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpCacheConfig {
+    /// 快取目錄，例如 `.cache/http`
+    pub directory: String,
+    pub on_not_modified: Option<NotModifiedPolicy>,
+}
+
+/// 伺服器回應 304 時的處理方式
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotModifiedPolicy {
+    /// 使用快取的回應內容
+    #[default]
+    UseCached,
+    /// 所有請求都是 304 時回報 `EtlError::NotModified`，由呼叫端略過本次執行
+    Skip,
+}
+
+/// HTTP 請求錄製與重播，通常由 `ETL_HTTP_FIXTURES` / `ETL_HTTP_FIXTURES_DIR` 環境變數啟用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpFixtureConfig {
+    pub mode: FixtureMode,
+    /// fixture 目錄（預設 `tests/fixtures/http`）
+    pub directory: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixtureMode {
+    /// 照常送出請求，並將每組請求與回應寫入 fixture 目錄
+    Record,
+    /// 只從 fixture 目錄回應，不連線；找不到對應的 fixture 時回報錯誤
+    Replay,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphQlSourceConfig {
+    pub url: String,
+    /// GraphQL 查詢，與 `query_file` 擇一
+    pub query: Option<String>,
+    pub query_file: Option<String>,
+    pub operation_name: Option<String>,
+    /// 查詢變數，字串值可使用 `${VAR}` 與日期運算式
+    pub variables: Option<serde_json::Map<String, serde_json::Value>>,
+    pub headers: Option<HashMap<String, String>>,
+    pub auth: Option<AuthConfig>,
+    pub retry: Option<RetryConfig>,
+    pub rate_limit: Option<RateLimitConfig>,
+    pub http: Option<HttpClientConfig>,
+    /// 記錄所在的節點，例如 `$.data.products.edges[*].node`
+    pub records_path: String,
+    pub pagination: Option<GraphQlPagination>,
+}
+
+/// Relay 風格的 cursor 分頁
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphQlPagination {
+    /// `pageInfo { hasNextPage endCursor }` 所在的路徑，例如 `$.data.products.pageInfo`
+    pub page_info_path: String,
+    /// 傳入 cursor 的查詢變數名稱（預設 `after`）
+    pub cursor_variable: Option<String>,
+    /// 最多抓取的頁數（預設 1000）
+    pub max_pages: Option<usize>,
+}
+
+/// API 請求 body，字串值可使用 `${VAR}` 與日期運算式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiRequestBody {
+    Json(serde_json::Value),
+    /// `application/x-www-form-urlencoded`
+    Form(HashMap<String, String>),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    /// 建立連線的逾時
+    pub connect_timeout_ms: Option<u64>,
+    /// 兩次讀取之間的逾時
+    pub read_timeout_ms: Option<u64>,
+    /// 整個請求（含讀取回應）的逾時，預設 30 秒
+    pub timeout_ms: Option<u64>,
+    pub proxy: Option<ProxyConfig>,
+    /// 額外信任的根憑證（PEM 檔案，可包含多張憑證）
+    pub ca_certificates: Option<Vec<String>>,
+    /// mTLS 用戶端憑證
+    pub client_certificate: Option<ClientCertificateConfig>,
+    pub http_version: Option<HttpVersion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// 例如 `http://proxy.corp.local:3128`，HTTP 與 HTTPS 請求都會經過
+    pub url: String,
+    /// 不經過代理的主機，例如 `["localhost", ".internal.corp", "10.0.0.0/8"]`
+    pub no_proxy: Option<Vec<String>>,
+    pub username: Option<String>,
+    pub password: Option<SecretRef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientCertificateConfig {
+    /// PEM 憑證（鏈）
+    pub cert_path: String,
+    /// PEM 私鑰，憑證檔已包含私鑰時可省略
+    pub key_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HttpVersion {
+    /// TLS 連線透過 ALPN 協商
+    #[default]
+    Auto,
+    Http1,
+    /// 透過 TLS ALPN 協商 HTTP/2，伺服器只支援 HTTP/1.1 時請求失敗
+    Http2,
+    /// 不經協商直接使用 HTTP/2（prior knowledge），適用於明文 h2c 或確定支援 HTTP/2 的伺服器
+    Http2PriorKnowledge,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// 每秒請求數（token bucket 補充速率），未設定時不限速
+    pub requests_per_second: Option<f64>,
+    /// 可累積的 token 數上限（預設為每秒請求數）
+    pub burst: Option<u32>,
+    /// 同時進行中的請求數上限
+    pub max_in_flight: Option<usize>,
+    /// 依 `X-RateLimit-Remaining` / `X-RateLimit-Reset` 標頭暫停（預設 true）
+    pub respect_headers: Option<bool>,
+    /// 依標頭暫停的上限秒數（預設 60）
+    pub max_pause_secs: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum FileFormat {
@@ -53,9 +241,20 @@ pub enum FileFormat {
     Zip {
         extract_path: Option<String>,
         target_files: Vec<String>,
+        password: Option<SecretRef>,
     },
 }
 
+/// 敏感資訊的來源，避免將密碼直接寫在配置文件中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretRef {
+    /// 從環境變數讀取
+    Env(String),
+    /// 從 secrets 檔案讀取（例如 /run/secrets/zip_password），會去除結尾換行
+    File(String),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum DatabaseDriver {
@@ -77,16 +276,133 @@ pub enum AuthType {
     BasicAuth,
     BearerToken,
     ApiKey,
+    #[serde(alias = "oauth2")]
     OAuth2,
+    /// 以共用密鑰對請求內容做 HMAC 簽章，設定於 `credentials.hmac`
+    Hmac,
+    /// AWS Signature Version 4，設定於 `credentials.sigv4`
+    #[serde(alias = "sigv4")]
+    AwsSigV4,
+    /// 先向登入端點送出帳號密碼，再以 session cookie 或取得的 token 存取，設定於 `credentials.session`
+    #[serde(alias = "login")]
+    Session,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AuthCredentials {
     pub username: Option<String>,
     pub password: Option<String>,
     pub token: Option<String>,
     pub api_key: Option<String>,
     pub header_name: Option<String>,
+    /// OAuth2 token endpoint
+    pub token_url: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub scopes: Option<Vec<String>>,
+    /// 設定時使用 refresh token grant，否則使用 client credentials grant
+    pub refresh_token: Option<String>,
+    pub hmac: Option<HmacSigningConfig>,
+    pub sigv4: Option<SigV4Config>,
+    pub session: Option<SessionLoginConfig>,
+}
+
+/// 登入流程：登入回應的 Set-Cookie 存入 cookie jar，並可從回應取出 token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLoginConfig {
+    pub login_url: String,
+    /// 預設 POST
+    pub method: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+    /// 登入請求 body，字串值中的 `{username}`、`{password}` 會被取代
+    pub body: Option<ApiRequestBody>,
+    pub username: Option<String>,
+    pub password: Option<SecretRef>,
+    /// 未設定時只使用 cookie
+    pub token: Option<SessionTokenConfig>,
+    /// session 有效秒數，到期前重新登入；未設定時直到 session 失效才重新登入
+    pub ttl_seconds: Option<u64>,
+    /// 視為 session 失效的狀態碼（預設 401、403）；被轉址到 `login_url` 也視為失效
+    pub relogin_statuses: Option<Vec<u16>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTokenConfig {
+    pub source: TokenSource,
+    /// 放 token 的請求標頭（預設 `Authorization`）
+    pub header_name: Option<String>,
+    /// 標頭值，`{token}` 會被取代（預設 `"Bearer {token}"`）
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TokenSource {
+    /// 登入回應 JSON 中的路徑，例如 `$.data.token`
+    JsonPath { path: String },
+    /// 登入回應的標頭
+    Header { name: String },
+}
+
+/// HMAC 簽章：依 `string_to_sign` 模板組出待簽字串，簽章與時間戳記放在標頭
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HmacSigningConfig {
+    pub key_id: Option<String>,
+    pub secret: SecretRef,
+    pub algorithm: Option<HmacAlgorithm>,
+    /// 可用 `{method}`、`{path}`、`{query}`、`{host}`、`{timestamp}`、`{nonce}`、`{key_id}`、
+    /// `{body}`、`{body_sha256}`（預設 `"{method}\n{path}\n{timestamp}\n{body}"`）
+    pub string_to_sign: Option<String>,
+    /// 簽章標頭名稱（預設 `X-Signature`）
+    pub signature_header: Option<String>,
+    /// 簽章標頭的值，可用 `{signature}`、`{key_id}`、`{timestamp}`（預設 `"{signature}"`）
+    pub signature_format: Option<String>,
+    /// 時間戳記標頭名稱（預設 `X-Timestamp`）
+    pub timestamp_header: Option<String>,
+    pub timestamp_format: Option<TimestampFormat>,
+    /// 設定時 `key_id` 另外放在此標頭
+    pub key_id_header: Option<String>,
+    /// 設定時每個請求產生隨機 nonce 放在此標頭
+    pub nonce_header: Option<String>,
+    pub encoding: Option<SignatureEncoding>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HmacAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    #[default]
+    UnixSeconds,
+    UnixMillis,
+    Rfc3339,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigV4Config {
+    pub region: String,
+    /// 例如 `s3`、`execute-api`
+    pub service: String,
+    /// 未設定時讀取 `AWS_ACCESS_KEY_ID`、`AWS_SECRET_ACCESS_KEY`、`AWS_SESSION_TOKEN`
+    pub credentials: Option<AwsCredentials>,
+    /// 以 `UNSIGNED-PAYLOAD` 取代 body 雜湊（僅 S3 支援）
+    pub unsigned_payload: Option<bool>,
+    /// 路徑是否再編碼一次（預設 S3 以外的服務為 true）
+    pub double_encode_path: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,6 +418,55 @@ pub struct RetryConfig {
     pub initial_delay_ms: u64,
     pub max_delay_ms: u64,
     pub backoff_multiplier: f64,
+    /// 重試等待時間是否加入隨機抖動（預設 true）
+    pub jitter: Option<bool>,
+    /// 需要重試的 HTTP 狀態碼（預設 429、502、503、504）
+    pub retry_on_status: Option<Vec<u16>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginationConfig {
+    #[serde(flatten)]
+    pub strategy: PaginationStrategy,
+    /// 最多抓取的頁數，避免 API 持續回傳下一頁時無限迴圈（預設 1000）
+    pub max_pages: Option<usize>,
+    /// 分頁參數放在查詢字串或 body（預設 query）
+    pub param_location: Option<ParamLocation>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamLocation {
+    #[default]
+    Query,
+    /// 寫入 JSON body 的頂層欄位或 form 欄位
+    Body,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PaginationStrategy {
+    /// `?page=1&limit=100`，回傳空頁或不足一頁時停止
+    PageNumber {
+        page_param: Option<String>,
+        size_param: Option<String>,
+        page_size: Option<u64>,
+        start_page: Option<u64>,
+    },
+    /// `?offset=0&limit=100`，可設定 `concurrency` 同時抓取多頁
+    Offset {
+        offset_param: Option<String>,
+        limit_param: Option<String>,
+        limit: u64,
+        concurrency: Option<usize>,
+    },
+    /// 從回應內容取得下一頁的 cursor（例如 `meta.next_cursor`），直到 cursor 為空
+    Cursor {
+        cursor_param: String,
+        cursor_path: String,
+    },
+    /// 依照 RFC 5988 `Link` 標頭中的 `rel="next"` 前進
+    LinkHeader,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,9 +478,18 @@ pub struct TransformationConfig {
     pub condition: Option<ConditionConfig>,
 }
 
+/// 程式 API（`MappingRule`）與 JSON 配置共用的轉換類型
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TransformationType {
+    /// 原值複製到目標欄位
+    Copy,
+    Uppercase,
+    Lowercase,
+    /// 從 mapping 檔案載入的查找表取值
+    Lookup {
+        table: String,
+    },
     Map {
         mapping: HashMap<String, String>,
     },
@@ -144,6 +518,42 @@ pub enum TransformationType {
         function: String,
         parameters: HashMap<String, serde_json::Value>,
     },
+    /// 以記錄欄位組成請求呼叫 API，將回應中選取的欄位合併到記錄
+    Enrich(Box<EnrichConfig>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnrichConfig {
+    /// URL 模板，`{field}` 以記錄欄位取代，例如 `https://api.example.com/items/{id}`
+    pub url: String,
+    pub method: Option<String>,
+    pub headers: Option<HashMap<String, String>>,
+    pub auth: Option<AuthConfig>,
+    pub retry: Option<RetryConfig>,
+    pub rate_limit: Option<RateLimitConfig>,
+    /// JSON body 模板；字串中的 `{field}` 以記錄欄位取代，整個字串為 `{field}` 時保留原始型別
+    pub body: Option<serde_json::Value>,
+    /// 目標欄位 -> 回應中的路徑（JSONPath 風格）
+    pub fields: HashMap<String, String>,
+    /// 同時進行的請求數（預設 4）
+    pub concurrency: Option<usize>,
+    #[serde(default)]
+    pub on_error: EnrichErrorPolicy,
+}
+
+/// 單筆記錄的 API 呼叫失敗時的處理方式
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnrichErrorPolicy {
+    /// 中止轉換並回報錯誤
+    #[default]
+    Fail,
+    /// 移除該筆記錄
+    Skip,
+    /// 目標欄位設為 null
+    Null,
+    /// 保留記錄但不寫入目標欄位
+    Keep,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -227,34 +637,80 @@ pub enum OutputFormat {
     Database {
         table_name: String,
         mode: WriteMode,
+        /// 唯一鍵欄位，`upsert` 模式必填；SurrealDB 以其值作為 record ID（多個欄位時為陣列 ID）
+        key_fields: Option<Vec<String>>,
     },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum OutputDestination {
     LocalFile {
         path: String,
         compress: Option<CompressionType>,
+        compression_options: Option<CompressionOptions>,
     },
     S3 {
         bucket: String,
+        /// 物件 key，可使用 `${VAR}`、`{timestamp}`（UTC `%Y%m%dT%H%M%SZ`）與 `{date}`（`%Y-%m-%d`）
         key: String,
         region: String,
         credentials: Option<AwsCredentials>,
+        /// S3 相容服務的網址，設定時預設使用 path-style
+        endpoint: Option<String>,
+        path_style: Option<bool>,
+        /// 預設依輸出格式（`text/csv`、`application/json`）
+        content_type: Option<String>,
+        /// 以 `x-amz-meta-<name>` 標頭寫入的物件 metadata，值可使用模板
+        metadata: Option<HashMap<String, String>>,
+        server_side_encryption: Option<ServerSideEncryption>,
+        /// multipart upload 每個分段的大小（MiB，最小 5，預設 8）；檔案較小時以單一 PUT 上傳
+        part_size_mb: Option<u64>,
+        /// 每個請求（含各分段）的重試設定（預設 3 次，重試 429、500、502、503、504）
+        retry: Option<RetryConfig>,
     },
     Database {
         connection_string: String,
         driver: DatabaseDriver,
+        /// SurrealDB 的 namespace 與 database（預設皆為 `etl`）
+        namespace: Option<String>,
+        database: Option<String>,
     },
     Api {
         url: String,
+        /// 預設 POST
         method: Option<String>,
         headers: Option<HashMap<String, String>>,
         auth: Option<AuthConfig>,
+        retry: Option<RetryConfig>,
+        /// 每批記錄的編碼方式（預設 JSON 陣列）
+        batch_format: Option<BatchFormat>,
+        /// JSON 陣列格式的 body 模板，`"{records}"` 會被該批記錄取代，例如 `{"items": "{records}"}`
+        body_template: Option<serde_json::Value>,
+        /// 失敗批次寫入的 NDJSON 檔案（預設 `dead_letter.ndjson`）
+        dead_letter_path: Option<String>,
     },
 }
 
+/// S3 伺服器端加密
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerSideEncryption {
+    /// SSE-S3
+    Aes256,
+    /// SSE-KMS，未指定 `kms_key_id` 時使用帳號預設的 KMS 金鑰
+    AwsKms { kms_key_id: Option<String> },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchFormat {
+    #[default]
+    JsonArray,
+    Ndjson,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum CompressionType {
@@ -262,6 +718,44 @@ pub enum CompressionType {
     Zip,
     Bzip2,
     Zstd,
+    Tar,
+    TarGz,
+}
+
+impl CompressionType {
+    /// 壓縮後輸出檔案的副檔名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CompressionType::Gzip => "gz",
+            CompressionType::Zip => "zip",
+            CompressionType::Bzip2 => "bz2",
+            CompressionType::Zstd => "zst",
+            CompressionType::Tar => "tar",
+            CompressionType::TarGz => "tar.gz",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompressionOptions {
+    /// ZIP 內部檔案使用的壓縮方法，預設為 deflate
+    pub method: Option<ZipMethod>,
+    /// 壓縮等級，範圍依壓縮方法而定（deflate/gzip: 0-9, bzip2: 1-9, zstd: 1-22）
+    pub level: Option<i64>,
+    /// 檔案權限（unix mode），預設為 0o644
+    pub file_permissions: Option<u32>,
+    /// 設定後 ZIP 以 AES-256 加密，其他壓縮格式不支援密碼
+    pub password: Option<SecretRef>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZipMethod {
+    Stored,
+    #[default]
+    Deflate,
+    Bzip2,
+    Zstd,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -288,6 +782,8 @@ pub struct GlobalSettings {
     pub log_level: Option<String>,
     pub timeout_seconds: Option<u64>,
     pub variables: Option<HashMap<String, String>>,
+    /// `lookup` 轉換使用的 mapping 檔案，由 `EtlPipeline::run_config` 載入
+    pub mapping_file: Option<String>,
 }
 
 impl EtlConfig {
@@ -1,27 +1,212 @@
 use crate::utils::error::{EtlError, Result};
-use crate::config::settings::{AuthConfig, AuthType, RetryConfig};
-use backoff::{ExponentialBackoff, future::retry};
-use reqwest::{Client, Response, Method, header::{HeaderMap, HeaderName, HeaderValue}};
+use crate::config::settings::{
+    ApiRequestBody, ApiSourceConfig, AuthConfig, AuthType, FixtureMode, HttpCacheConfig,
+    HttpClientConfig, HttpFixtureConfig, HttpVersion, NotModifiedPolicy, PaginationConfig,
+    PaginationStrategy, ParamLocation, RateLimitConfig, ResponseFormat, RetryConfig,
+};
+use crate::extractors::http_cache::HttpCache;
+use crate::extractors::http_fixtures::HttpFixtures;
+use crate::extractors::oauth2::OAuth2TokenProvider;
+use crate::extractors::payload::Download;
+use crate::extractors::rate_limiter::RateLimiter;
+use crate::extractors::retry::RetryPolicy;
+use crate::extractors::session::SessionManager;
+use crate::extractors::signing;
+use crate::utils::helpers::{extract_records, resolve_secret, select_path};
+use crate::utils::template::TemplateContext;
+use reqwest::{Client, Response, Method, StatusCode, header::{HeaderMap, HeaderName, HeaderValue}};
 use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use std::str::FromStr;
+use tracing::{debug, warn};
+
+pub(crate) const DEFAULT_MAX_PAGES: usize = 1000;
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 未指定記錄路徑時，包裝物件中常見的記錄陣列欄位
+const COMMON_RECORD_KEYS: [&str; 5] = ["data", "items", "results", "records", "rows"];
+
+/// 請求 body
+#[derive(Debug, Clone)]
+pub enum RequestBody {
+    Json(serde_json::Value),
+    /// `application/x-www-form-urlencoded`
+    Form(Vec<(String, String)>),
+    /// 已序列化的內容，例如 NDJSON
+    Raw { content_type: String, data: Vec<u8> },
+}
 
 pub struct ApiClient {
     client: Client,
     default_timeout: Duration,
+    oauth2: OAuth2TokenProvider,
+    sessions: SessionManager,
+    http_config: HttpClientConfig,
+    /// 登入請求不跟隨轉址，才能取得 302 回應上的 Set-Cookie
+    login_client: OnceLock<Client>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    cache: Option<HttpCache>,
+    fixtures: Option<HttpFixtures>,
+}
+
+/// API 資料源的一頁回應
+struct Page {
+    body: serde_json::Value,
+    next_link: Option<String>,
+    /// 伺服器回應 304，內容來自快取
+    not_modified: bool,
+}
+
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ApiClient {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .expect("Failed to create HTTP client");
+        Self::with_http_config(&HttpClientConfig::default()).expect("Failed to create HTTP client")
+    }
+
+    /// 依逾時、代理伺服器與 TLS 設定建立 client，並依 `ETL_HTTP_FIXTURES` 啟用錄製／重播
+    pub fn with_http_config(config: &HttpClientConfig) -> Result<Self> {
+        let default_timeout = config.timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_TIMEOUT);
+
+        Ok(Self {
+            client: Self::build_client(config, default_timeout, true)?,
+            default_timeout,
+            oauth2: OAuth2TokenProvider::new(),
+            sessions: SessionManager::new(),
+            http_config: config.clone(),
+            login_client: OnceLock::new(),
+            rate_limiter: None,
+            cache: None,
+            fixtures: HttpFixtures::from_env(),
+        })
+    }
+
+    /// 依 API 資料源設定建立 client，速率限制由該資料源的所有請求共用
+    pub fn for_source(source: &ApiSourceConfig) -> Result<Self> {
+        Self::validate_pagination(source.pagination.as_ref())?;
+        let client = Self::with_options(source.http.as_ref(), source.rate_limit.as_ref())?;
+        match &source.cache {
+            Some(cache) => client.with_cache(cache),
+            None => Ok(client),
+        }
+    }
+
+    pub fn with_cache(mut self, config: &HttpCacheConfig) -> Result<Self> {
+        self.cache = Some(HttpCache::new(config)?);
+        Ok(self)
+    }
+
+    pub fn with_fixtures(mut self, config: &HttpFixtureConfig) -> Self {
+        self.fixtures = Some(HttpFixtures::new(config));
+        self
+    }
+
+    fn is_replaying(&self) -> bool {
+        self.fixtures.as_ref().is_some_and(|f| f.mode() == FixtureMode::Replay)
+    }
+
+    pub fn with_options(
+        http: Option<&HttpClientConfig>,
+        rate_limit: Option<&RateLimitConfig>,
+    ) -> Result<Self> {
+        let client = match http {
+            Some(http) => Self::with_http_config(http)?,
+            None => Self::new(),
+        };
+        Ok(match rate_limit {
+            Some(rate_limit) => client.with_rate_limit(rate_limit),
+            None => client,
+        })
+    }
+
+    fn build_client(config: &HttpClientConfig, timeout: Duration, follow_redirects: bool) -> Result<Client> {
+        let client_error = |what: &str, e: reqwest::Error| {
+            EtlError::ConfigError(format!("Invalid {}: {}", what, e))
+        };
+        let read_pem = |path: &str| {
+            std::fs::read(path).map_err(|e| {
+                EtlError::ConfigError(format!("Failed to read PEM file {}: {}", path, e))
+            })
+        };
+
+        let mut builder = Client::builder().timeout(timeout);
+        if !follow_redirects {
+            builder = builder.redirect(reqwest::redirect::Policy::none());
+        }
+        if let Some(ms) = config.connect_timeout_ms {
+            builder = builder.connect_timeout(Duration::from_millis(ms));
+        }
+        if let Some(ms) = config.read_timeout_ms {
+            builder = builder.read_timeout(Duration::from_millis(ms));
+        }
+
+        if let Some(proxy_config) = &config.proxy {
+            let mut proxy = reqwest::Proxy::all(&proxy_config.url)
+                .map_err(|e| client_error("proxy url", e))?;
+            if let Some(no_proxy) = proxy_config.no_proxy.as_ref().filter(|hosts| !hosts.is_empty()) {
+                proxy = proxy.no_proxy(reqwest::NoProxy::from_string(&no_proxy.join(",")));
+            }
+            if let Some(username) = &proxy_config.username {
+                let password = proxy_config.password.as_ref().map(resolve_secret).transpose()?;
+                proxy = proxy.basic_auth(username, password.as_deref().unwrap_or_default());
+            }
+            builder = builder.proxy(proxy);
+        }
+
+        for path in config.ca_certificates.iter().flatten() {
+            let certificates = reqwest::Certificate::from_pem_bundle(&read_pem(path)?)
+                .map_err(|e| client_error("CA certificate", e))?;
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some(client_certificate) = &config.client_certificate {
+            // rustls 的 Identity 需要憑證與私鑰在同一份 PEM 中
+            let mut pem = read_pem(&client_certificate.cert_path)?;
+            if let Some(key_path) = &client_certificate.key_path {
+                pem.push(b'\n');
+                pem.extend(read_pem(key_path)?);
+            }
+            let identity = reqwest::Identity::from_pem(&pem)
+                .map_err(|e| client_error("client certificate", e))?;
+            builder = builder.use_rustls_tls().identity(identity);
+        }
+
+        builder = match config.http_version.clone().unwrap_or_default() {
+            // Http2 同樣以 ALPN 協商，由每個請求要求 HTTP/2
+            HttpVersion::Auto | HttpVersion::Http2 => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        };
+
+        builder.build().map_err(|e| client_error("HTTP client configuration", e))
+    }
+
+    fn login_client(&self) -> Result<&Client> {
+        if let Some(client) = self.login_client.get() {
+            return Ok(client);
+        }
+        let client = Self::build_client(&self.http_config, self.default_timeout, false)?;
+        Ok(self.login_client.get_or_init(|| client))
+    }
 
-        Self {
-            client,
-            default_timeout: Duration::from_secs(30),
+    pub fn with_rate_limit(mut self, config: &RateLimitConfig) -> Self {
+        self.rate_limiter = Some(Arc::new(RateLimiter::new(config)));
+        self
+    }
+
+    /// 使用其他 client 共用的速率限制器，取代 `rate_limit` 設定建立的限制器
+    pub fn with_shared_rate_limit(mut self, limiter: Option<Arc<RateLimiter>>) -> Self {
+        if limiter.is_some() {
+            self.rate_limiter = limiter;
         }
+        self
     }
 
     pub async fn fetch_with_config(
@@ -31,6 +216,69 @@ impl ApiClient {
         headers: Option<HashMap<String, String>>,
         auth: Option<AuthConfig>,
         retry_config: Option<RetryConfig>,
+    ) -> Result<Response> {
+        self.fetch_with_body(url, method, headers, auth, retry_config, None).await
+    }
+
+    /// 與 `fetch_with_config` 相同，另外可附帶 body
+    pub async fn fetch_with_body(
+        &self,
+        url: &str,
+        method: Option<String>,
+        headers: Option<HashMap<String, String>>,
+        auth: Option<AuthConfig>,
+        retry_config: Option<RetryConfig>,
+        body: Option<RequestBody>,
+    ) -> Result<Response> {
+        self.send_request(url, method, headers, auth, retry_config, body, false).await
+    }
+
+    /// 與 `fetch_with_body` 相同，但非 2xx 回應也原樣回傳，由呼叫端讀取錯誤內容
+    pub async fn fetch_unchecked(
+        &self,
+        url: &str,
+        method: Option<String>,
+        headers: Option<HashMap<String, String>>,
+        auth: Option<AuthConfig>,
+        retry_config: Option<RetryConfig>,
+        body: Option<RequestBody>,
+    ) -> Result<Response> {
+        self.send_unchecked(url, method, headers, auth, retry_config, body).await
+    }
+
+    /// `allow_not_modified` 為 true 時，304 視為成功回傳給呼叫端
+    #[allow(clippy::too_many_arguments)]
+    async fn send_request(
+        &self,
+        url: &str,
+        method: Option<String>,
+        headers: Option<HashMap<String, String>>,
+        auth: Option<AuthConfig>,
+        retry_config: Option<RetryConfig>,
+        body: Option<RequestBody>,
+        allow_not_modified: bool,
+    ) -> Result<Response> {
+        let response = self.send_unchecked(url, method, headers, auth, retry_config, body).await?;
+
+        let not_modified = allow_not_modified && response.status() == StatusCode::NOT_MODIFIED;
+        if !response.status().is_success() && !not_modified {
+            return Err(EtlError::HttpError(
+                response.status().as_u16(),
+                format!("HTTP request failed: {}", response.status()),
+            ));
+        }
+
+        Ok(response)
+    }
+
+    async fn send_unchecked(
+        &self,
+        url: &str,
+        method: Option<String>,
+        headers: Option<HashMap<String, String>>,
+        auth: Option<AuthConfig>,
+        retry_config: Option<RetryConfig>,
+        body: Option<RequestBody>,
     ) -> Result<Response> {
         let method = method
             .as_ref()
@@ -39,7 +287,10 @@ impl ApiClient {
             .map_err(|e| EtlError::ConfigError(format!("Invalid HTTP method: {}", e)))?
             .unwrap_or(Method::GET);
 
-        let mut request_builder = self.client.request(method, url);
+        let mut request_builder = self.client.request(method, url).timeout(self.default_timeout);
+        if self.http_config.http_version == Some(HttpVersion::Http2) {
+            request_builder = request_builder.version(reqwest::Version::HTTP_2);
+        }
 
         // 添加自定義標頭
         if let Some(headers) = headers {
@@ -54,47 +305,114 @@ impl ApiClient {
             request_builder = request_builder.headers(header_map);
         }
 
-        // 添加認證
-        if let Some(auth_config) = auth {
-            request_builder = self.apply_auth(request_builder, auth_config)?;
+        request_builder = match body {
+            Some(RequestBody::Json(value)) => request_builder.json(&value),
+            Some(RequestBody::Form(fields)) => request_builder.form(&fields),
+            Some(RequestBody::Raw { content_type, data }) => request_builder
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .body(data),
+            None => request_builder,
+        };
+
+        // 重播時不連線，也就不需要取得認證資訊（CI 不必提供密鑰）
+        let auth = auth.filter(|_| !self.is_replaying());
+        let mut response = self.send_with_retry(&request_builder, auth.as_ref(), retry_config.as_ref()).await?;
+
+        // OAuth2 token 可能在到期前被撤銷、session 可能逾時，重新取得後重試一次
+        if let Some(auth_config) = &auth {
+            if self.auth_expired(auth_config, &response) {
+                warn!("Credentials for {} expired ({}), re-authenticating and retrying", url, response.status());
+                self.invalidate_auth(auth_config).await;
+                response = self.send_with_retry(&request_builder, Some(auth_config), retry_config.as_ref()).await?;
+            }
+            if let (AuthType::Session, Some(session)) = (&auth_config.auth_type, &auth_config.credentials.session) {
+                self.sessions.observe(session, &response).await;
+            }
         }
 
-        let backoff = if let Some(config) = retry_config {
-            ExponentialBackoff {
-                current_interval: Duration::from_millis(config.initial_delay_ms),
-                max_elapsed_time: Some(Duration::from_millis(config.max_delay_ms * config.max_attempts as u64)),
-                ..Default::default()
+        Ok(response)
+    }
+
+    /// 每次嘗試都重新套用認證，HMAC nonce、時間戳記與 SigV4 簽章不會在重試間重複使用
+    async fn send_with_retry(
+        &self,
+        request_builder: &reqwest::RequestBuilder,
+        auth: Option<&AuthConfig>,
+        retry_config: Option<&RetryConfig>,
+    ) -> Result<Response> {
+        if let Some(fixtures) = self.fixtures.as_ref().filter(|_| self.is_replaying()) {
+            return fixtures.replay(&Self::clone_request(request_builder)?.build()?);
+        }
+
+        let policy = RetryPolicy::from_config(retry_config);
+        let max_attempts = policy.max_attempts();
+        let mut attempt = 1;
+
+        loop {
+            let has_attempts_left = attempt < max_attempts;
+            let permit = match &self.rate_limiter {
+                Some(limiter) => Some(limiter.acquire().await),
+                None => None,
+            };
+
+            // 取得許可後才簽章，等待速率限制的時間不會讓簽章過期
+            let request = match auth {
+                Some(auth_config) => self.apply_auth(Self::clone_request(request_builder)?, auth_config).await?,
+                None => Self::clone_request(request_builder)?,
             }
-        } else {
-            ExponentialBackoff::default()
-        };
+            .build()?;
+            let target = format!("{} {}", request.method(), request.url());
+            // 錄製模式只寫入最後一次嘗試的回應
+            let recorded = self.fixtures.as_ref().and_then(|_| request.try_clone());
 
-        let response = retry(backoff, || async {
-            let request = request_builder
-                .try_clone()
-                .ok_or_else(|| EtlError::RequestError("Failed to clone request".to_string()))?;
-            
-            request
-                .send()
-                .await
-                .map_err(|e| {
-                    if e.is_timeout() || e.is_connect() {
-                        backoff::Error::transient(e.into())
-                    } else {
-                        backoff::Error::permanent(e.into())
-                    }
-                })
-        })
-        .await?;
+            let result = self.client.execute(request).await;
+            drop(permit);
 
-        if !response.status().is_success() {
-            return Err(EtlError::HttpError(
-                response.status().as_u16(),
-                format!("HTTP request failed: {}", response.status()),
-            ));
+            if let (Some(limiter), Ok(response)) = (&self.rate_limiter, &result) {
+                limiter.observe(response.headers()).await;
+            }
+
+            let delay = match result {
+                Ok(response) if has_attempts_left && policy.is_retryable_status(response.status()) => {
+                    let delay = policy
+                        .retry_after(response.headers())
+                        .unwrap_or_else(|| policy.delay_for(attempt));
+                    warn!(
+                        "Attempt {}/{} {} returned {}, retrying in {:?}",
+                        attempt, max_attempts, target, response.status(), delay
+                    );
+                    delay
+                }
+                Ok(response) => {
+                    debug!("Attempt {}/{} {} returned {}", attempt, max_attempts, target, response.status());
+                    return match (&self.fixtures, recorded) {
+                        (Some(fixtures), Some(request)) => fixtures.record(&request, response).await,
+                        _ => Ok(response),
+                    };
+                }
+                Err(e) if has_attempts_left && (e.is_timeout() || e.is_connect()) => {
+                    let delay = policy.delay_for(attempt);
+                    warn!(
+                        "Attempt {}/{} {} failed: {}, retrying in {:?}",
+                        attempt, max_attempts, target, e, delay
+                    );
+                    delay
+                }
+                Err(e) => {
+                    warn!("Attempt {}/{} {} failed: {}", attempt, max_attempts, target, e);
+                    return Err(e.into());
+                }
+            };
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
         }
+    }
 
-        Ok(response)
+    fn clone_request(request_builder: &reqwest::RequestBuilder) -> Result<reqwest::RequestBuilder> {
+        request_builder
+            .try_clone()
+            .ok_or_else(|| EtlError::RequestError("Failed to clone request".to_string()))
     }
 
     pub async fn fetch_json(
@@ -136,16 +454,462 @@ impl ApiClient {
         Ok(bytes.to_vec())
     }
 
-    fn apply_auth(
+    /// 將回應串流寫入暫存檔，適合大型匯出檔案
+    pub async fn download(
+        &self,
+        url: &str,
+        method: Option<String>,
+        headers: Option<HashMap<String, String>>,
+        auth: Option<AuthConfig>,
+        retry_config: Option<RetryConfig>,
+        body: Option<RequestBody>,
+    ) -> Result<Download> {
+        let response = self.fetch_with_body(url, method, headers, auth, retry_config, body).await?;
+        Download::from_response(response).await
+    }
+
+    /// 抓取 API 資料源的所有記錄，依設定處理分頁與記錄路徑
+    pub async fn fetch_source(&self, source: &ApiSourceConfig) -> Result<Vec<serde_json::Value>> {
+        let format = source.response_format.unwrap_or_default();
+        if format != ResponseFormat::Json {
+            if source.pagination.is_some() {
+                return Err(EtlError::ConfigError(
+                    "Pagination requires response_format json".to_string(),
+                ));
+            }
+            return self.fetch_payload(source, format).await;
+        }
+
+        Self::validate_pagination(source.pagination.as_ref())?;
+        let (records, changed) = match &source.pagination {
+            Some(pagination) => self.fetch_paginated(source, pagination).await?,
+            None => {
+                let url = Self::source_url(source)?;
+                let page = self.fetch_page(source, &url, &[], ParamLocation::Query).await?;
+                let records = extract_records(
+                    page.body,
+                    source.records_path.as_deref(),
+                    source.envelope_fields.as_ref(),
+                )?;
+                (records, !page.not_modified)
+            }
+        };
+
+        let skip_unchanged = self
+            .cache
+            .as_ref()
+            .is_some_and(|cache| *cache.on_not_modified() == NotModifiedPolicy::Skip);
+        if !changed && skip_unchanged {
+            return Err(EtlError::NotModified(format!(
+                "{} has not changed since the last run",
+                source.url
+            )));
+        }
+        Ok(records)
+    }
+
+    /// 下載非 JSON（或格式未知）的回應，依格式解析
+    async fn fetch_payload(
+        &self,
+        source: &ApiSourceConfig,
+        format: ResponseFormat,
+    ) -> Result<Vec<serde_json::Value>> {
+        let url = Self::source_url(source)?;
+        let body = Self::request_body(source.body.as_ref(), &[])?;
+        let download = self
+            .download(
+                &url,
+                Self::source_method(source, body.as_ref()),
+                source.headers.clone(),
+                source.auth.clone(),
+                source.retry.clone(),
+                body,
+            )
+            .await?;
+        download.parse(format, source.records_path.as_deref(), source.envelope_fields.as_ref())
+    }
+
+    /// 每頁筆數為 0 時 offset 不會前進、頁碼分頁也無法判斷結尾，在送出請求前回報設定錯誤
+    fn validate_pagination(pagination: Option<&PaginationConfig>) -> Result<()> {
+        let zero_size = match pagination.map(|p| &p.strategy) {
+            Some(PaginationStrategy::Offset { limit: 0, .. }) => Some("limit"),
+            Some(PaginationStrategy::PageNumber { page_size: Some(0), .. }) => Some("page_size"),
+            _ => None,
+        };
+        match zero_size {
+            Some(field) => Err(EtlError::ConfigError(format!(
+                "Pagination {} must be greater than 0",
+                field
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// 依照分頁設定抓取所有頁面，回傳串接後的記錄，以及是否有任何一頁不是 304
+    async fn fetch_paginated(
+        &self,
+        source: &ApiSourceConfig,
+        pagination: &PaginationConfig,
+    ) -> Result<(Vec<serde_json::Value>, bool)> {
+        let url = Self::source_url(source)?;
+        let url = url.as_str();
+        let max_pages = pagination.max_pages.unwrap_or(DEFAULT_MAX_PAGES);
+        let location = pagination.param_location.unwrap_or_default();
+
+        let mut records = Vec::new();
+        let mut pages = 0;
+        let mut changed = false;
+
+        match &pagination.strategy {
+            PaginationStrategy::PageNumber { page_param, size_param, page_size, start_page } => {
+                let page_param = page_param.as_deref().unwrap_or("page");
+                let mut page_number = start_page.unwrap_or(1);
+
+                while pages < max_pages {
+                    let mut params = vec![(page_param, serde_json::Value::from(page_number))];
+                    if let (Some(size_param), Some(size)) = (size_param, page_size) {
+                        params.push((size_param.as_str(), serde_json::Value::from(*size)));
+                    }
+                    let page = self.fetch_page(source, url, &params, location).await?;
+                    changed |= !page.not_modified;
+                    let items = Self::page_records(source, page.body)?;
+                    let count = items.len() as u64;
+                    records.extend(items);
+                    pages += 1;
+
+                    if count == 0 || page_size.is_some_and(|size| count < size) {
+                        return Ok((records, changed));
+                    }
+                    page_number += 1;
+                }
+            }
+            PaginationStrategy::Offset { offset_param, limit_param, limit, concurrency } => {
+                let offset_param = offset_param.as_deref().unwrap_or("offset");
+                let limit_param = limit_param.as_deref().unwrap_or("limit");
+                let concurrency = concurrency.unwrap_or(1).max(1);
+                let mut offset = 0;
+
+                while pages < max_pages {
+                    let batch = concurrency.min(max_pages - pages);
+                    let page_params: Vec<_> = (0..batch as u64)
+                        .map(|i| {
+                            vec![
+                                (offset_param, serde_json::Value::from(offset + i * limit)),
+                                (limit_param, serde_json::Value::from(*limit)),
+                            ]
+                        })
+                        .collect();
+
+                    let batch_pages = futures::future::try_join_all(
+                        page_params
+                            .iter()
+                            .map(|params| self.fetch_page(source, url, params, location)),
+                    )
+                    .await?;
+                    for page in batch_pages {
+                        changed |= !page.not_modified;
+                        let items = Self::page_records(source, page.body)?;
+                        let count = items.len() as u64;
+                        records.extend(items);
+                        pages += 1;
+
+                        // 第一個不足一頁的回應代表已到結尾，同批次後續頁面一律捨棄
+                        if count < *limit {
+                            return Ok((records, changed));
+                        }
+                    }
+                    offset += batch as u64 * limit;
+                }
+            }
+            PaginationStrategy::Cursor { cursor_param, cursor_path } => {
+                let mut params = Vec::new();
+
+                while pages < max_pages {
+                    let page = self.fetch_page(source, url, &params, location).await?;
+                    changed |= !page.not_modified;
+                    let cursor = select_path(&page.body, cursor_path)
+                        .filter(|c| !c.is_null() && c.as_str() != Some(""))
+                        .cloned();
+                    records.extend(Self::page_records(source, page.body)?);
+                    pages += 1;
+
+                    match cursor {
+                        Some(cursor) => params = vec![(cursor_param.as_str(), cursor)],
+                        None => return Ok((records, changed)),
+                    }
+                }
+            }
+            PaginationStrategy::LinkHeader => {
+                let mut page_url = url.to_string();
+
+                while pages < max_pages {
+                    let page = self.fetch_page(source, &page_url, &[], location).await?;
+                    changed |= !page.not_modified;
+                    records.extend(Self::page_records(source, page.body)?);
+                    pages += 1;
+
+                    match page.next_link {
+                        Some(next) => page_url = next,
+                        None => return Ok((records, changed)),
+                    }
+                }
+            }
+        }
+
+        warn!("Pagination for {} stopped at the max_pages cap ({})", url, max_pages);
+        Ok((records, changed))
+    }
+
+    /// 送出 API 資料源的一個請求，`params`（分頁參數）依 `location` 放在查詢字串或 body。
+    /// 啟用快取時發送條件式請求，304 時使用快取的內容
+    async fn fetch_page(
+        &self,
+        source: &ApiSourceConfig,
+        url: &str,
+        params: &[(&str, serde_json::Value)],
+        location: ParamLocation,
+    ) -> Result<Page> {
+        let (url, body) = match location {
+            ParamLocation::Query if !params.is_empty() => {
+                let query: Vec<(&str, String)> =
+                    params.iter().map(|(k, v)| (*k, Self::param_string(v))).collect();
+                (Self::with_query(url, &query)?, Self::request_body(source.body.as_ref(), &[])?)
+            }
+            ParamLocation::Query => (url.to_string(), Self::request_body(source.body.as_ref(), &[])?),
+            ParamLocation::Body => (url.to_string(), Self::request_body(source.body.as_ref(), params)?),
+        };
+        let method = Self::source_method(source, body.as_ref());
+
+        let cache_key = self.cache.as_ref().map(|_| {
+            HttpCache::key(
+                method.as_deref().unwrap_or("GET"),
+                &url,
+                source.headers.as_ref(),
+                source.auth.as_ref(),
+                body.as_ref(),
+            )
+        });
+        let cached = match (&self.cache, &cache_key) {
+            (Some(cache), Some(key)) => cache.load(key),
+            _ => None,
+        };
+        let mut headers = source.headers.clone();
+        if let Some((entry, _)) = &cached {
+            headers.get_or_insert_with(HashMap::new).extend(entry.conditional_headers());
+        }
+
+        let response = self
+            .send_request(
+                &url,
+                method,
+                headers,
+                source.auth.clone(),
+                source.retry.clone(),
+                body,
+                cached.is_some(),
+            )
+            .await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some((entry, cached_body)) = cached {
+                debug!("{} not modified since {}, using cached response", url, entry.stored_at);
+                return Ok(Page {
+                    body: Self::parse_body(&url, &cached_body)?,
+                    next_link: entry.next_link,
+                    not_modified: true,
+                });
+            }
+        }
+
+        let next_link = Self::next_link(&response);
+        let response_headers = response.headers().clone();
+        let text = response.text().await?;
+        let body = Self::parse_body(&url, &text)?;
+        if let (Some(cache), Some(key)) = (&self.cache, &cache_key) {
+            cache.store(key, &url, &response_headers, next_link.clone(), &text)?;
+        }
+        Ok(Page { body, next_link, not_modified: false })
+    }
+
+    /// 資料源 URL 加上 `query_params`
+    fn source_url(source: &ApiSourceConfig) -> Result<String> {
+        match source.query_params.as_ref().filter(|params| !params.is_empty()) {
+            Some(params) => {
+                let mut params: Vec<(&str, String)> =
+                    params.iter().map(|(k, v)| (k.as_str(), v.clone())).collect();
+                params.sort();
+                Self::with_query(&source.url, &params)
+            }
+            None => Ok(source.url.clone()),
+        }
+    }
+
+    fn request_body(
+        body: Option<&ApiRequestBody>,
+        params: &[(&str, serde_json::Value)],
+    ) -> Result<Option<RequestBody>> {
+        match body {
+            Some(ApiRequestBody::Form(fields)) => {
+                let mut pairs: Vec<(String, String)> = fields
+                    .iter()
+                    .filter(|(k, _)| !params.iter().any(|(name, _)| name == k))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                pairs.sort();
+                pairs.extend(params.iter().map(|(k, v)| (k.to_string(), Self::param_string(v))));
+                Ok(Some(RequestBody::Form(pairs)))
+            }
+            Some(ApiRequestBody::Json(value)) if params.is_empty() => Ok(Some(RequestBody::Json(value.clone()))),
+            None if params.is_empty() => Ok(None),
+            json => {
+                let mut value = match json {
+                    Some(ApiRequestBody::Json(value)) => value.clone(),
+                    _ => serde_json::Value::Object(serde_json::Map::new()),
+                };
+                let object = value.as_object_mut().ok_or_else(|| {
+                    EtlError::ConfigError(
+                        "Pagination parameters in the body require a JSON object body".to_string(),
+                    )
+                })?;
+                for (name, param) in params {
+                    object.insert(name.to_string(), param.clone());
+                }
+                Ok(Some(RequestBody::Json(value)))
+            }
+        }
+    }
+
+    fn param_string(value: &serde_json::Value) -> String {
+        match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// 展開資料源中 url、query_params、headers 與 body 的 `${VAR}` 與日期運算式
+    pub fn render_source(source: &ApiSourceConfig, context: &TemplateContext) -> Result<ApiSourceConfig> {
+        let mut rendered = source.clone();
+        rendered.url = context.render(&source.url)?;
+        rendered.query_params = source.query_params.as_ref().map(|p| context.render_map(p)).transpose()?;
+        rendered.headers = source.headers.as_ref().map(|h| context.render_map(h)).transpose()?;
+        rendered.body = match &source.body {
+            Some(ApiRequestBody::Json(value)) => Some(ApiRequestBody::Json(context.render_value(value)?)),
+            Some(ApiRequestBody::Form(fields)) => Some(ApiRequestBody::Form(context.render_map(fields)?)),
+            None => None,
+        };
+        Ok(rendered)
+    }
+
+    fn page_records(source: &ApiSourceConfig, body: serde_json::Value) -> Result<Vec<serde_json::Value>> {
+        if source.records_path.is_some() || source.envelope_fields.is_some() {
+            return extract_records(
+                body,
+                source.records_path.as_deref(),
+                source.envelope_fields.as_ref(),
+            );
+        }
+
+        let records = match body {
+            serde_json::Value::Array(items) => items,
+            serde_json::Value::Object(mut map) => {
+                let key = COMMON_RECORD_KEYS
+                    .iter()
+                    .find(|key| map.get(**key).is_some_and(|v| v.is_array()));
+                match key.and_then(|key| map.remove(*key)) {
+                    Some(serde_json::Value::Array(items)) => items,
+                    _ => vec![serde_json::Value::Object(map)],
+                }
+            }
+            serde_json::Value::Null => Vec::new(),
+            other => vec![other],
+        };
+        Ok(records)
+    }
+
+    fn parse_body(url: &str, body: &str) -> Result<serde_json::Value> {
+        if body.trim().is_empty() {
+            return Err(EtlError::ParseError(format!("Empty response from {}", url)));
+        }
+        serde_json::from_str(body)
+            .map_err(|e| EtlError::ParseError(format!("Invalid JSON response from {}: {}", url, e)))
+    }
+
+    /// 有 body 而未指定 method 時使用 POST
+    fn source_method(source: &ApiSourceConfig, body: Option<&RequestBody>) -> Option<String> {
+        source.method.clone().or_else(|| body.map(|_| "POST".to_string()))
+    }
+
+    /// 設定（或取代）URL 的查詢參數
+    fn with_query(url: &str, params: &[(&str, String)]) -> Result<String> {
+        let mut parsed = reqwest::Url::parse(url)
+            .map_err(|e| EtlError::ConfigError(format!("Invalid URL '{}': {}", url, e)))?;
+        let existing: Vec<(String, String)> = parsed
+            .query_pairs()
+            .filter(|(key, _)| !params.iter().any(|(name, _)| name == key))
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect();
+
+        parsed
+            .query_pairs_mut()
+            .clear()
+            .extend_pairs(existing)
+            .extend_pairs(params.iter().map(|(k, v)| (*k, v.as_str())));
+        debug!("Requesting page {}", parsed);
+        Ok(parsed.to_string())
+    }
+
+    /// 解析 RFC 5988 `Link` 標頭，取得 `rel="next"` 的網址
+    fn next_link(response: &Response) -> Option<String> {
+        response
+            .headers()
+            .get_all(reqwest::header::LINK)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .find_map(|link| {
+                let mut parts = link.split(';').map(str::trim);
+                let target = parts.next()?.strip_prefix('<')?.strip_suffix('>')?;
+                let is_next = parts.any(|param| {
+                    param
+                        .strip_prefix("rel=")
+                        .map(|rel| rel.trim_matches('"').split_whitespace().any(|r| r == "next"))
+                        .unwrap_or(false)
+                });
+                if !is_next {
+                    return None;
+                }
+                // 相對路徑以目前請求的網址為基準
+                response.url().join(target).ok().map(|url| url.to_string())
+            })
+    }
+
+    fn auth_expired(&self, auth_config: &AuthConfig, response: &Response) -> bool {
+        match (&auth_config.auth_type, &auth_config.credentials.session) {
+            (AuthType::OAuth2, _) => response.status() == StatusCode::UNAUTHORIZED,
+            (AuthType::Session, Some(session)) => SessionManager::is_expired(session, response),
+            _ => false,
+        }
+    }
+
+    async fn invalidate_auth(&self, auth_config: &AuthConfig) {
+        match (&auth_config.auth_type, &auth_config.credentials.session) {
+            (AuthType::OAuth2, _) => self.oauth2.invalidate(&auth_config.credentials).await,
+            (AuthType::Session, Some(session)) => self.sessions.invalidate(session).await,
+            _ => {}
+        }
+    }
+
+    async fn apply_auth(
         &self,
         mut request_builder: reqwest::RequestBuilder,
-        auth_config: AuthConfig,
+        auth_config: &AuthConfig,
     ) -> Result<reqwest::RequestBuilder> {
+        let credentials = auth_config.credentials.clone();
         match auth_config.auth_type {
             AuthType::BasicAuth => {
                 if let (Some(username), Some(password)) = (
-                    auth_config.credentials.username,
-                    auth_config.credentials.password,
+                    credentials.username,
+                    credentials.password,
                 ) {
                     request_builder = request_builder.basic_auth(username, Some(password));
                 } else {
@@ -155,7 +919,7 @@ impl ApiClient {
                 }
             }
             AuthType::BearerToken => {
-                if let Some(token) = auth_config.credentials.token {
+                if let Some(token) = credentials.token {
                     request_builder = request_builder.bearer_auth(token);
                 } else {
                     return Err(EtlError::ConfigError(
@@ -165,8 +929,8 @@ impl ApiClient {
             }
             AuthType::ApiKey => {
                 if let (Some(api_key), Some(header_name)) = (
-                    auth_config.credentials.api_key,
-                    auth_config.credentials.header_name,
+                    credentials.api_key,
+                    credentials.header_name,
                 ) {
                     request_builder = request_builder.header(header_name, api_key);
                 } else {
@@ -176,12 +940,288 @@ impl ApiClient {
                 }
             }
             AuthType::OAuth2 => {
-                return Err(EtlError::ConfigError(
-                    "OAuth2 authentication not yet implemented".to_string(),
-                ));
+                let token = self.oauth2.access_token(&self.client, &credentials).await?;
+                request_builder = request_builder.bearer_auth(token);
+            }
+            // 簽章涵蓋 method、URL、標頭與 body，需要先組出完整請求
+            AuthType::Hmac => {
+                let hmac = credentials.hmac.as_ref().ok_or_else(|| {
+                    EtlError::ConfigError("HMAC auth requires credentials.hmac".to_string())
+                })?;
+                let request = Self::clone_request(&request_builder)?.build()?;
+                request_builder = request_builder.headers(signing::hmac_headers(&request, hmac)?);
+            }
+            AuthType::AwsSigV4 => {
+                let sigv4 = credentials.sigv4.as_ref().ok_or_else(|| {
+                    EtlError::ConfigError("SigV4 auth requires credentials.sigv4".to_string())
+                })?;
+                let request = Self::clone_request(&request_builder)?.build()?;
+                request_builder = request_builder.headers(signing::sigv4_headers(&request, sigv4)?);
+            }
+            AuthType::Session => {
+                let session = credentials.session.as_ref().ok_or_else(|| {
+                    EtlError::ConfigError("Session auth requires credentials.session".to_string())
+                })?;
+                let request = Self::clone_request(&request_builder)?.build()?;
+                let headers = self.sessions.headers(self.login_client()?, session, request.url()).await?;
+                request_builder = request_builder.headers(headers);
             }
         }
 
         Ok(request_builder)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::{AuthCredentials, HmacSigningConfig, SecretRef};
+    use crate::test_support::{TestResponse, TestServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn retry(max_attempts: u32) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            initial_delay_ms: 1,
+            max_delay_ms: 10,
+            backoff_multiplier: 1.0,
+            jitter: Some(false),
+            retry_on_status: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn signs_each_retry_attempt() {
+        let calls = AtomicUsize::new(0);
+        let server = TestServer::start(move |_| match calls.fetch_add(1, Ordering::SeqCst) {
+            0 => TestResponse::new(503, "busy"),
+            _ => TestResponse::json(200, serde_json::json!([])),
+        })
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let secret = dir.path().join("secret");
+        std::fs::write(&secret, "s3cr3t").unwrap();
+        let auth = AuthConfig {
+            auth_type: AuthType::Hmac,
+            credentials: AuthCredentials {
+                hmac: Some(HmacSigningConfig {
+                    key_id: None,
+                    secret: SecretRef::File(secret.to_string_lossy().to_string()),
+                    algorithm: None,
+                    string_to_sign: Some("{method}\n{path}\n{nonce}".to_string()),
+                    signature_header: None,
+                    signature_format: None,
+                    timestamp_header: None,
+                    timestamp_format: None,
+                    key_id_header: None,
+                    nonce_header: Some("X-Nonce".to_string()),
+                    encoding: None,
+                }),
+                ..Default::default()
+            },
+        };
+
+        ApiClient::new()
+            .fetch_json(&server.url, None, None, Some(auth), Some(retry(3)))
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            assert_eq!((request.method.as_str(), request.path(), request.query()), ("GET", "/", ""));
+            assert!(request.body_text().is_empty());
+        }
+        assert_ne!(requests[0].header("x-nonce"), requests[1].header("x-nonce"));
+        assert_ne!(requests[0].header("x-signature"), requests[1].header("x-signature"));
+    }
+
+    #[tokio::test]
+    async fn http2_does_not_fall_back_to_http1() {
+        let server = TestServer::start(|_| TestResponse::json(200, serde_json::json!([]))).await;
+        let client = |http_version| {
+            ApiClient::with_http_config(&HttpClientConfig { http_version: Some(http_version), ..Default::default() })
+                .unwrap()
+        };
+
+        // 明文連線沒有 ALPN，伺服器只會以 HTTP/1.1 回應
+        let result = client(HttpVersion::Http2).fetch_json(&server.url, None, None, None, Some(retry(1))).await;
+        assert!(result.is_err());
+        assert!(server.requests().is_empty());
+
+        client(HttpVersion::Auto).fetch_json(&server.url, None, None, None, None).await.unwrap();
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn cursor_is_injected_into_a_rendered_post_body() {
+        let server = TestServer::start(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            match body.get("cursor").and_then(|c| c.as_str()) {
+                None => TestResponse::json(200, serde_json::json!({ "items": [{ "id": 1 }], "next": "p2" })),
+                Some("p2") => TestResponse::json(200, serde_json::json!({ "items": [{ "id": 2 }], "next": null })),
+                Some(other) => panic!("unexpected cursor {}", other),
+            }
+        })
+        .await;
+        let source: ApiSourceConfig = serde_json::from_value(serde_json::json!({
+            "url": format!("{}/search", server.url),
+            "body": { "json": { "since": "${LAST_RUN_DATE+1d}", "size": 1 } },
+            "records_path": "$.items",
+            "pagination": {
+                "type": "cursor",
+                "cursor_param": "cursor",
+                "cursor_path": "$.next",
+                "param_location": "body"
+            }
+        }))
+        .unwrap();
+        let variables = HashMap::from([("LAST_RUN_DATE".to_string(), "2024-02-28".to_string())]);
+        let source = ApiClient::render_source(&source, &TemplateContext::new(variables)).unwrap();
+
+        let records = ApiClient::new().fetch_source(&source).await.unwrap();
+
+        assert_eq!(records, vec![serde_json::json!({ "id": 1 }), serde_json::json!({ "id": 2 })]);
+        let requests = server.requests();
+        assert!(requests.iter().all(|r| r.method == "POST" && r.query().is_empty()));
+        let bodies: Vec<serde_json::Value> =
+            requests.iter().map(|r| serde_json::from_slice(&r.body).unwrap()).collect();
+        assert_eq!(bodies[0], serde_json::json!({ "since": "2024-02-29", "size": 1 }));
+        assert_eq!(bodies[1], serde_json::json!({ "since": "2024-02-29", "size": 1, "cursor": "p2" }));
+    }
+
+    fn paginated_source(url: &str, pagination: serde_json::Value) -> ApiSourceConfig {
+        serde_json::from_value(serde_json::json!({
+            "url": format!("{}/items", url),
+            "records_path": "$.items",
+            "pagination": pagination
+        }))
+        .unwrap()
+    }
+
+    /// `offset`/`limit` 查詢參數對應到 `total` 筆 id 從 0 開始的資料
+    fn offset_page(request: &crate::test_support::RecordedRequest, total: u64) -> TestResponse {
+        let param = |name: &str| {
+            request
+                .query()
+                .split('&')
+                .find_map(|pair| pair.strip_prefix(&format!("{}=", name)))
+                .map(|v| v.parse::<u64>().unwrap())
+                .unwrap()
+        };
+        let (offset, limit) = (param("offset"), param("limit"));
+        let items: Vec<_> = (offset..(offset + limit).min(total)).map(|id| serde_json::json!({ "id": id })).collect();
+        TestResponse::json(200, serde_json::json!({ "items": items }))
+    }
+
+    fn ids(records: &[serde_json::Value]) -> Vec<u64> {
+        records.iter().map(|r| r["id"].as_u64().unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn offset_pagination_stops_at_a_short_page() {
+        let server = TestServer::start(|request| offset_page(request, 5)).await;
+        let source = paginated_source(&server.url, serde_json::json!({ "type": "offset", "limit": 2 }));
+
+        let records = ApiClient::new().fetch_source(&source).await.unwrap();
+
+        assert_eq!(ids(&records), vec![0, 1, 2, 3, 4]);
+        let queries: Vec<_> = server.requests().iter().map(|r| r.query().to_string()).collect();
+        assert_eq!(queries, vec!["offset=0&limit=2", "offset=2&limit=2", "offset=4&limit=2"]);
+    }
+
+    #[tokio::test]
+    async fn concurrent_offset_batches_discard_pages_after_the_first_short_one() {
+        // offset=2 回傳不足一頁，同批次 offset=4 的資料即使存在也要捨棄
+        let server = TestServer::start(|request| {
+            if request.query().starts_with("offset=2&") {
+                TestResponse::json(200, serde_json::json!({ "items": [{ "id": 2 }] }))
+            } else {
+                offset_page(request, 100)
+            }
+        })
+        .await;
+        let source = paginated_source(&server.url, serde_json::json!({ "type": "offset", "limit": 2, "concurrency": 3 }));
+
+        let records = ApiClient::new().fetch_source(&source).await.unwrap();
+
+        assert_eq!(ids(&records), vec![0, 1, 2]);
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn concurrent_offset_batches_continue_while_pages_are_full() {
+        let server = TestServer::start(|request| offset_page(request, 7)).await;
+        let source = paginated_source(&server.url, serde_json::json!({ "type": "offset", "limit": 2, "concurrency": 2 }));
+
+        let records = ApiClient::new().fetch_source(&source).await.unwrap();
+
+        assert_eq!(ids(&records), (0..7).collect::<Vec<_>>());
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn cursor_pagination_follows_the_cursor_path_until_it_is_empty() {
+        let server = TestServer::start(|request| match request.query() {
+            "" => TestResponse::json(200, serde_json::json!({ "items": [{ "id": 1 }], "meta": { "next": "abc" } })),
+            "after=abc" => TestResponse::json(200, serde_json::json!({ "items": [{ "id": 2 }], "meta": { "next": "" } })),
+            other => panic!("unexpected query {}", other),
+        })
+        .await;
+        let source = paginated_source(
+            &server.url,
+            serde_json::json!({ "type": "cursor", "cursor_param": "after", "cursor_path": "meta.next" }),
+        );
+
+        let records = ApiClient::new().fetch_source(&source).await.unwrap();
+
+        assert_eq!(ids(&records), vec![1, 2]);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn link_header_pagination_follows_rel_next() {
+        let server = TestServer::start(|request| {
+            let page: u64 = request.query().strip_prefix("page=").map(|p| p.parse().unwrap()).unwrap_or(1);
+            let response = TestResponse::json(200, serde_json::json!({ "items": [{ "id": page }] }));
+            if page < 3 {
+                let next = format!("</items?page={}>; rel=\"next\", </items?page=3>; rel=\"last\"", page + 1);
+                response.header("Link", &next)
+            } else {
+                response
+            }
+        })
+        .await;
+        let source = paginated_source(&server.url, serde_json::json!({ "type": "link_header" }));
+
+        let records = ApiClient::new().fetch_source(&source).await.unwrap();
+
+        assert_eq!(ids(&records), vec![1, 2, 3]);
+        let targets: Vec<_> = server.requests().iter().map(|r| r.target.clone()).collect();
+        assert_eq!(targets, vec!["/items", "/items?page=2", "/items?page=3"]);
+    }
+
+    #[tokio::test]
+    async fn zero_page_sizes_are_rejected_before_any_request() {
+        let server = TestServer::start(|_| TestResponse::json(200, serde_json::json!({ "items": [] }))).await;
+        let cases = [
+            (serde_json::json!({ "type": "offset", "limit": 0 }), "limit"),
+            (serde_json::json!({ "type": "page_number", "size_param": "size", "page_size": 0 }), "page_size"),
+        ];
+
+        for (pagination, field) in cases {
+            let source = paginated_source(&server.url, pagination);
+            let expected = format!("Pagination {} must be greater than 0", field);
+            match ApiClient::for_source(&source) {
+                Err(EtlError::ConfigError(message)) => assert_eq!(message, expected),
+                other => panic!("expected ConfigError, got {:?}", other.map(|_| ())),
+            }
+            match ApiClient::new().fetch_source(&source).await {
+                Err(EtlError::ConfigError(message)) => assert_eq!(message, expected),
+                other => panic!("expected ConfigError, got {:?}", other),
+            }
+        }
+        assert!(server.requests().is_empty());
+    }
+}
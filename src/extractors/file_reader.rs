@@ -1,17 +1,24 @@
 use crate::utils::error::{EtlError, Result};
 use crate::config::settings::FileFormat;
 use crate::models::data_types::DataRecord;
+use crate::utils::helpers::{extract_records, resolve_secret};
 use csv::ReaderBuilder;
 use std::collections::HashMap;
 use std::fs::{File, read_to_string};
-use std::io::BufReader;
+use std::io::{BufReader, Read, Seek};
 use zip::ZipArchive;
-use flate2::read::GzDecoder;
 
 pub struct FileReader {
+    #[allow(dead_code)] // 目前僅支援 UTF-8
     encoding: String,
 }
 
+impl Default for FileReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FileReader {
     pub fn new() -> Self {
         Self {
@@ -42,16 +49,58 @@ impl FileReader {
             FileFormat::Parquet => {
                 Err(EtlError::ConfigError("Parquet format not yet implemented".to_string()))
             }
-            FileFormat::Zip { extract_path: _, target_files } => {
-                self.read_zip(path, target_files).await
+            FileFormat::Zip { extract_path: _, target_files, password } => {
+                let password = password.as_ref().map(resolve_secret).transpose()?;
+                self.read_zip(path, target_files, password.as_deref()).await
             }
         }
     }
 
     async fn read_json(&self, path: &str) -> Result<Vec<DataRecord>> {
+        self.read_json_selected(path, None, None).await
+    }
+
+    /// 讀取 JSON 檔案，只取 `records_path` 指向的節點並附加外層欄位
+    pub async fn read_json_selected(
+        &self,
+        path: &str,
+        records_path: Option<&str>,
+        envelope_fields: Option<&HashMap<String, String>>,
+    ) -> Result<Vec<DataRecord>> {
         let content = read_to_string(path)?;
         let json_value: serde_json::Value = serde_json::from_str(&content)?;
-        
+        if records_path.is_none() && envelope_fields.is_none() {
+            return self.parse_json(json_value);
+        }
+        self.parse_json(serde_json::Value::Array(extract_records(
+            json_value,
+            records_path,
+            envelope_fields,
+        )?))
+    }
+
+    async fn read_csv(
+        &self,
+        path: &str,
+        delimiter: char,
+        has_headers: bool,
+    ) -> Result<Vec<DataRecord>> {
+        let file = File::open(path)?;
+        self.parse_csv(BufReader::new(file), delimiter, has_headers)
+    }
+
+    async fn read_zip(
+        &self,
+        path: &str,
+        target_files: Vec<String>,
+        password: Option<&str>,
+    ) -> Result<Vec<DataRecord>> {
+        let file = File::open(path)?;
+        self.parse_zip(BufReader::new(file), &target_files, password)
+    }
+
+    /// 將 JSON 陣列或物件轉為記錄，其他型別視為錯誤
+    pub fn parse_json(&self, json_value: serde_json::Value) -> Result<Vec<DataRecord>> {
         match json_value {
             serde_json::Value::Array(array) => {
                 let mut records = Vec::new();
@@ -67,15 +116,12 @@ impl FileReader {
         }
     }
 
-    async fn read_csv(
+    pub fn parse_csv<R: Read>(
         &self,
-        path: &str,
+        reader: R,
         delimiter: char,
         has_headers: bool,
     ) -> Result<Vec<DataRecord>> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        
         let mut csv_reader = ReaderBuilder::new()
             .delimiter(delimiter as u8)
             .has_headers(has_headers)
@@ -107,13 +153,21 @@ impl FileReader {
         Ok(records)
     }
 
-    async fn read_zip(&self, path: &str, target_files: Vec<String>) -> Result<Vec<DataRecord>> {
-        let file = File::open(path)?;
-        let mut archive = ZipArchive::new(file)?;
+    /// 解析 ZIP 中符合 `target_files` 的 CSV/JSON 檔案，`target_files` 為空時處理全部
+    pub fn parse_zip<R: Read + Seek>(
+        &self,
+        reader: R,
+        target_files: &[String],
+        password: Option<&str>,
+    ) -> Result<Vec<DataRecord>> {
+        let mut archive = ZipArchive::new(reader)?;
         let mut all_records = Vec::new();
 
         for i in 0..archive.len() {
-            let mut file = archive.by_index(i)?;
+            let mut file = match password {
+                Some(password) => archive.by_index_decrypt(i, password.as_bytes())?,
+                None => archive.by_index(i)?,
+            };
             let file_name = file.name().to_string();
 
             let should_process = if target_files.is_empty() {
@@ -127,29 +181,18 @@ impl FileReader {
             if should_process {
                 let mut contents = Vec::new();
                 std::io::copy(&mut file, &mut contents)?;
-                let contents = String::from_utf8(contents).map_err(|e| {
-                    EtlError::ParseError(format!("Invalid UTF-8 in file {}: {}", file_name, e))
-                })?;
                 
                 if file_name.ends_with(".csv") {
-                    let temp_path = format!("temp_{}", file_name);
-                    std::fs::write(&temp_path, &contents)?;
-                    
-                    let records = self.read_csv(&temp_path, ',', true).await?;
+                    let records = self.parse_csv(contents.as_slice(), ',', true)?;
                     all_records.extend(records);
-                    
-                    let _ = std::fs::remove_file(&temp_path);
-                    
                 } else if file_name.ends_with(".json") {
-                    let json_value: serde_json::Value = serde_json::from_str(&contents)?;
+                    let json_value: serde_json::Value = serde_json::from_slice(&contents)
+                        .map_err(|e| {
+                            EtlError::ParseError(format!("Invalid JSON in file {}: {}", file_name, e))
+                        })?;
                     match json_value {
-                        serde_json::Value::Array(array) => {
-                            for (index, item) in array.into_iter().enumerate() {
-                                all_records.push(self.json_to_record(item, index)?);
-                            }
-                        }
-                        serde_json::Value::Object(_) => {
-                            all_records.push(self.json_to_record(json_value, 0)?);
+                        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                            all_records.extend(self.parse_json(json_value)?);
                         }
                         _ => continue,
                     }
@@ -208,4 +251,35 @@ impl FileReader {
             filename == pattern
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn read_json_selected_uses_records_path_and_envelope_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        std::fs::write(
+            &path,
+            r#"{ "report": { "date": "2024-05-01", "rows": [{ "sku": "A1" }, { "sku": "B2" }] } }"#,
+        )
+        .unwrap();
+        let envelope = HashMap::from([("report_date".to_string(), "$.report.date".to_string())]);
+
+        let records = FileReader::new()
+            .read_json_selected(path.to_str().unwrap(), Some("$['report'].rows[*]"), Some(&envelope))
+            .await
+            .unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].fields["sku"], "B2");
+        assert_eq!(records[1].fields["report_date"], "2024-05-01");
+
+        let error = FileReader::new()
+            .read_json_selected(path.to_str().unwrap(), Some("$.report.items"), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, EtlError::ParseError(_)), "{:?}", error);
+    }
+}
@@ -0,0 +1,310 @@
+use crate::config::settings::GraphQlSourceConfig;
+use crate::extractors::api_client::{ApiClient, RequestBody, DEFAULT_MAX_PAGES};
+use crate::extractors::rate_limiter::RateLimiter;
+use crate::utils::error::{EtlError, Result};
+use crate::utils::helpers::{extract_records, select_path};
+use crate::utils::template::TemplateContext;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+const DEFAULT_CURSOR_VARIABLE: &str = "after";
+
+/// 透過 `ApiClient` 執行 GraphQL 查詢，依 Relay `pageInfo` 逐頁抓取記錄
+pub struct GraphQlExtractor {
+    client: ApiClient,
+}
+
+impl GraphQlExtractor {
+    pub fn for_source(source: &GraphQlSourceConfig) -> Result<Self> {
+        Ok(Self {
+            client: ApiClient::with_options(source.http.as_ref(), source.rate_limit.as_ref())?,
+        })
+    }
+
+    pub fn with_shared_rate_limit(mut self, limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.client = self.client.with_shared_rate_limit(limiter);
+        self
+    }
+
+    pub async fn fetch(&self, source: &GraphQlSourceConfig) -> Result<Vec<serde_json::Value>> {
+        let query = Self::load_query(source)?;
+        let mut variables = source.variables.clone().unwrap_or_default();
+        let max_pages = source
+            .pagination
+            .as_ref()
+            .and_then(|p| p.max_pages)
+            .unwrap_or(DEFAULT_MAX_PAGES);
+
+        let mut records = Vec::new();
+        for page in 1..=max_pages {
+            let data = self.execute(source, &query, &variables).await?;
+            let next_cursor = match &source.pagination {
+                Some(pagination) => Self::next_cursor(&data, &pagination.page_info_path)?,
+                None => None,
+            };
+            records.extend(extract_records(data, Some(&source.records_path), None)?);
+
+            match (&source.pagination, next_cursor) {
+                (Some(pagination), Some(cursor)) => {
+                    debug!("GraphQL page {} done, continuing after cursor {}", page, cursor);
+                    let name = pagination.cursor_variable.as_deref().unwrap_or(DEFAULT_CURSOR_VARIABLE);
+                    variables.insert(name.to_string(), cursor);
+                }
+                _ => return Ok(records),
+            }
+        }
+
+        warn!("GraphQL pagination for {} stopped at the max_pages cap ({})", source.url, max_pages);
+        Ok(records)
+    }
+
+    /// 送出查詢並回傳整個回應（含 `data`），`errors` 不為空時回報 `ApiError`；
+    /// 伺服器常以 4xx 搭配 `errors` 回報查詢錯誤，因此先解析 `errors` 再檢查狀態碼
+    async fn execute(
+        &self,
+        source: &GraphQlSourceConfig,
+        query: &str,
+        variables: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<serde_json::Value> {
+        let mut payload = serde_json::json!({
+            "query": query,
+            "variables": variables,
+        });
+        if let Some(operation_name) = &source.operation_name {
+            payload["operationName"] = serde_json::Value::String(operation_name.clone());
+        }
+
+        let response = self
+            .client
+            .fetch_unchecked(
+                &source.url,
+                Some("POST".to_string()),
+                source.headers.clone(),
+                source.auth.clone(),
+                source.retry.clone(),
+                Some(RequestBody::Json(payload)),
+            )
+            .await?;
+        let status = response.status();
+        let text = response.text().await?;
+        let body = serde_json::from_str::<serde_json::Value>(&text);
+
+        let errors = body.as_ref().ok().and_then(|b| b.get("errors")).and_then(|e| e.as_array());
+        if let Some(errors) = errors.filter(|e| !e.is_empty()) {
+            return Err(EtlError::ApiError(format!(
+                "GraphQL query failed: {}",
+                Self::error_messages(errors)
+            )));
+        }
+        if !status.is_success() {
+            return Err(EtlError::HttpError(
+                status.as_u16(),
+                format!("HTTP request failed: {}", status),
+            ));
+        }
+        body.map_err(|e| EtlError::ParseError(format!("Invalid GraphQL response from {}: {}", source.url, e)))
+    }
+
+    /// `hasNextPage` 為 true 且有 `endCursor` 時回傳下一頁的 cursor
+    fn next_cursor(data: &serde_json::Value, page_info_path: &str) -> Result<Option<serde_json::Value>> {
+        let page_info = select_path(data, page_info_path).ok_or_else(|| {
+            EtlError::ParseError(format!("GraphQL response has no pageInfo at '{}'", page_info_path))
+        })?;
+        let has_next = page_info
+            .get("hasNextPage")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        Ok(page_info
+            .get("endCursor")
+            .filter(|cursor| has_next && !cursor.is_null())
+            .cloned())
+    }
+
+    /// 合併 `errors[].message`，有 `path` 時一併列出
+    fn error_messages(errors: &[serde_json::Value]) -> String {
+        errors
+            .iter()
+            .map(|error| {
+                let message = error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| error.to_string());
+                match error.get("path").and_then(|p| p.as_array()) {
+                    Some(path) => {
+                        let path: Vec<String> = path
+                            .iter()
+                            .map(|segment| match segment {
+                                serde_json::Value::String(s) => s.clone(),
+                                other => other.to_string(),
+                            })
+                            .collect();
+                        format!("{} (at {})", message, path.join("."))
+                    }
+                    None => message,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    fn load_query(source: &GraphQlSourceConfig) -> Result<String> {
+        match (&source.query, &source.query_file) {
+            (Some(query), None) => Ok(query.clone()),
+            (None, Some(path)) => std::fs::read_to_string(path).map_err(|e| {
+                EtlError::ConfigError(format!("Failed to read GraphQL query file {}: {}", path, e))
+            }),
+            _ => Err(EtlError::ConfigError(
+                "GraphQL source requires exactly one of query or query_file".to_string(),
+            )),
+        }
+    }
+
+    /// 展開 url、headers 與查詢變數中的 `${VAR}` 與日期運算式
+    pub fn render_source(
+        source: &GraphQlSourceConfig,
+        context: &TemplateContext,
+    ) -> Result<GraphQlSourceConfig> {
+        let mut rendered = source.clone();
+        rendered.url = context.render(&source.url)?;
+        rendered.headers = source.headers.as_ref().map(|h| context.render_map(h)).transpose()?;
+        rendered.variables = source
+            .variables
+            .as_ref()
+            .map(|variables| {
+                variables
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), context.render_value(v)?)))
+                    .collect::<Result<serde_json::Map<_, _>>>()
+            })
+            .transpose()?;
+        Ok(rendered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{TestResponse, TestServer};
+
+    const QUERY: &str = "query($after: String) { products(after: $after) { edges { node { id } } pageInfo { hasNextPage endCursor } } }";
+
+    fn source(url: &str, overrides: serde_json::Value) -> GraphQlSourceConfig {
+        let mut config = serde_json::json!({
+            "url": format!("{}/graphql", url),
+            "query": QUERY,
+            "variables": { "first": 2 },
+            "records_path": "$.data.products.edges[*].node",
+            "pagination": { "page_info_path": "$.data.products.pageInfo" }
+        });
+        config.as_object_mut().unwrap().extend(overrides.as_object().unwrap().clone());
+        serde_json::from_value(config).unwrap()
+    }
+
+    fn page(ids: &[u64], end_cursor: Option<&str>) -> serde_json::Value {
+        let edges: Vec<_> = ids.iter().map(|id| serde_json::json!({ "node": { "id": id } })).collect();
+        serde_json::json!({
+            "data": { "products": {
+                "edges": edges,
+                "pageInfo": { "hasNextPage": end_cursor.is_some(), "endCursor": end_cursor }
+            } }
+        })
+    }
+
+    async fn fetch(source: &GraphQlSourceConfig) -> Result<Vec<serde_json::Value>> {
+        GraphQlExtractor::for_source(source)?.fetch(source).await
+    }
+
+    #[tokio::test]
+    async fn relay_pagination_passes_the_end_cursor_as_after() {
+        let server = TestServer::start(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            match body["variables"]["after"].as_str() {
+                None => TestResponse::json(200, page(&[1, 2], Some("c2"))),
+                Some("c2") => TestResponse::json(200, page(&[3], None)),
+                Some(other) => panic!("unexpected cursor {}", other),
+            }
+        })
+        .await;
+
+        let records = fetch(&source(&server.url, serde_json::json!({}))).await.unwrap();
+
+        let ids: Vec<_> = records.iter().map(|r| r["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, "POST");
+        let second: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(second["query"], QUERY);
+        assert_eq!(second["variables"], serde_json::json!({ "first": 2, "after": "c2" }));
+    }
+
+    #[tokio::test]
+    async fn errors_array_is_an_api_error_even_with_a_400_status() {
+        let server = TestServer::start(|_| {
+            TestResponse::json(
+                400,
+                serde_json::json!({
+                    "errors": [
+                        { "message": "Cannot query field \"sku\"", "path": ["products", 0, "sku"] },
+                        { "message": "Rate limited" }
+                    ]
+                }),
+            )
+        })
+        .await;
+
+        match fetch(&source(&server.url, serde_json::json!({}))).await {
+            Err(EtlError::ApiError(message)) => assert_eq!(
+                message,
+                "GraphQL query failed: Cannot query field \"sku\" (at products.0.sku); Rate limited"
+            ),
+            other => panic!("expected ApiError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn non_success_status_without_errors_is_an_http_error() {
+        let server = TestServer::start(|_| TestResponse::new(502, "bad gateway")).await;
+
+        match fetch(&source(&server.url, serde_json::json!({}))).await {
+            Err(EtlError::HttpError(status, _)) => assert_eq!(status, 502),
+            other => panic!("expected HttpError, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_page_info_is_a_parse_error() {
+        let server = TestServer::start(|_| {
+            TestResponse::json(200, serde_json::json!({ "data": { "products": { "edges": [] } } }))
+        })
+        .await;
+
+        match fetch(&source(&server.url, serde_json::json!({}))).await {
+            Err(EtlError::ParseError(message)) => {
+                assert!(message.contains("no pageInfo at '$.data.products.pageInfo'"), "{}", message)
+            }
+            other => panic!("expected ParseError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn query_and_query_file_are_mutually_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let query_file = dir.path().join("products.graphql");
+        std::fs::write(&query_file, QUERY).unwrap();
+        let query_file = query_file.display().to_string();
+
+        let from_file = source("http://localhost", serde_json::json!({ "query": null, "query_file": query_file }));
+        assert_eq!(GraphQlExtractor::load_query(&from_file).unwrap(), QUERY);
+
+        let both = source("http://localhost", serde_json::json!({ "query_file": query_file }));
+        let neither = source("http://localhost", serde_json::json!({ "query": null }));
+        for config in [both, neither] {
+            match GraphQlExtractor::load_query(&config) {
+                Err(EtlError::ConfigError(message)) => assert!(message.contains("exactly one of query or query_file")),
+                other => panic!("expected ConfigError, got {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::config::settings::{AuthConfig, HttpCacheConfig, NotModifiedPolicy};
+use crate::extractors::api_client::RequestBody;
+use crate::utils::error::Result;
+use reqwest::header::{HeaderMap, ETAG, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// 快取的回應驗證資訊，回應內容另存為 `<key>.body`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// `Link` 標頭中的下一頁網址，讓 304 時仍可繼續分頁
+    pub next_link: Option<String>,
+    pub stored_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl CacheEntry {
+    /// 條件式請求標頭
+    pub fn conditional_headers(&self) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        if let Some(etag) = &self.etag {
+            headers.insert("If-None-Match".to_string(), etag.clone());
+        }
+        if let Some(last_modified) = &self.last_modified {
+            headers.insert("If-Modified-Since".to_string(), last_modified.clone());
+        }
+        headers
+    }
+}
+
+/// 以 URL（含 method、標頭、認證身分與 body）為鍵，將 `ETag` / `Last-Modified` 與回應內容存在本機目錄
+#[derive(Debug)]
+pub struct HttpCache {
+    directory: PathBuf,
+    on_not_modified: NotModifiedPolicy,
+}
+
+impl HttpCache {
+    pub fn new(config: &HttpCacheConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.directory)?;
+        Ok(Self {
+            directory: PathBuf::from(&config.directory),
+            on_not_modified: config.on_not_modified.clone().unwrap_or_default(),
+        })
+    }
+
+    pub fn on_not_modified(&self) -> &NotModifiedPolicy {
+        &self.on_not_modified
+    }
+
+    /// 不同帳號或標頭（例如 `Accept-Language`、租戶 ID）可能取得不同內容，因此一併納入雜湊
+    pub fn key(
+        method: &str,
+        url: &str,
+        headers: Option<&HashMap<String, String>>,
+        auth: Option<&AuthConfig>,
+        body: Option<&RequestBody>,
+    ) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(method.to_uppercase().as_bytes());
+        hasher.update(b" ");
+        hasher.update(url.as_bytes());
+        let mut headers: Vec<(String, &str)> = headers
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| (name.to_lowercase(), value.as_str()))
+            .collect();
+        headers.sort();
+        for (name, value) in headers {
+            hasher.update(format!("\n{}: {}", name, value).as_bytes());
+        }
+        // 認證設定（含 token、帳號等）只以雜湊形式出現在檔名中
+        if let Some(auth) = auth {
+            hasher.update(b"\nauth ");
+            hasher.update(serde_json::to_string(auth).unwrap_or_default().as_bytes());
+        }
+        hasher.update(b"\n\n");
+        match body {
+            Some(RequestBody::Json(value)) => hasher.update(value.to_string().as_bytes()),
+            Some(RequestBody::Form(fields)) => {
+                for (name, value) in fields {
+                    hasher.update(format!("\n{}={}", name, value).as_bytes());
+                }
+            }
+            Some(RequestBody::Raw { content_type, data }) => {
+                hasher.update(content_type.as_bytes());
+                hasher.update(data);
+            }
+            None => {}
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// 讀取快取；檔案不存在或損毀時視為沒有快取
+    pub fn load(&self, key: &str) -> Option<(CacheEntry, String)> {
+        let (meta_path, body_path) = self.paths(key);
+        let meta = std::fs::read_to_string(&meta_path).ok()?;
+        let entry = match serde_json::from_str::<CacheEntry>(&meta) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!("Ignoring corrupted cache entry {}: {}", meta_path.display(), e);
+                return None;
+            }
+        };
+        let body = std::fs::read_to_string(&body_path).ok()?;
+        Some((entry, body))
+    }
+
+    /// 回應帶有 `ETag` 或 `Last-Modified` 時才寫入快取
+    pub fn store(
+        &self,
+        key: &str,
+        url: &str,
+        headers: &HeaderMap,
+        next_link: Option<String>,
+        body: &str,
+    ) -> Result<()> {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let entry = CacheEntry {
+            url: url.to_string(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            next_link,
+            stored_at: chrono::Utc::now(),
+        };
+        if entry.etag.is_none() && entry.last_modified.is_none() {
+            return Ok(());
+        }
+
+        // 先寫 body 再寫 metadata，metadata 存在即代表快取完整
+        let (meta_path, body_path) = self.paths(key);
+        Self::write_atomic(&body_path, body.as_bytes())?;
+        Self::write_atomic(&meta_path, serde_json::to_string_pretty(&entry)?.as_bytes())?;
+        debug!("Cached response for {} ({})", url, key);
+        Ok(())
+    }
+
+    fn paths(&self, key: &str) -> (PathBuf, PathBuf) {
+        (
+            self.directory.join(format!("{}.json", key)),
+            self.directory.join(format!("{}.body", key)),
+        )
+    }
+
+    fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::{AuthCredentials, AuthType};
+
+    const URL: &str = "https://api.example.com/v1/products?page=1";
+
+    fn bearer(token: &str) -> AuthConfig {
+        AuthConfig {
+            auth_type: AuthType::BearerToken,
+            credentials: AuthCredentials { token: Some(token.to_string()), ..Default::default() },
+        }
+    }
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn key_depends_on_auth_identity() {
+        let alice = HttpCache::key("GET", URL, None, Some(&bearer("alice")), None);
+        assert_eq!(alice, HttpCache::key("get", URL, None, Some(&bearer("alice")), None));
+        assert_ne!(alice, HttpCache::key("GET", URL, None, Some(&bearer("bob")), None));
+        assert_ne!(alice, HttpCache::key("GET", URL, None, None, None));
+        assert!(!alice.contains("alice"));
+    }
+
+    #[test]
+    fn key_depends_on_request_headers() {
+        let english = headers(&[("Accept-Language", "en"), ("X-Tenant", "a")]);
+        let key = HttpCache::key("GET", URL, Some(&english), None, None);
+
+        let reordered = headers(&[("x-tenant", "a"), ("accept-language", "en")]);
+        assert_eq!(key, HttpCache::key("GET", URL, Some(&reordered), None, None));
+        let other_tenant = headers(&[("Accept-Language", "en"), ("X-Tenant", "b")]);
+        assert_ne!(key, HttpCache::key("GET", URL, Some(&other_tenant), None, None));
+        assert_ne!(key, HttpCache::key("GET", URL, None, None, None));
+    }
+
+    #[test]
+    fn key_depends_on_body() {
+        let page_one = RequestBody::Json(serde_json::json!({"page": 1}));
+        let page_two = RequestBody::Json(serde_json::json!({"page": 2}));
+        assert_ne!(
+            HttpCache::key("POST", URL, None, None, Some(&page_one)),
+            HttpCache::key("POST", URL, None, None, Some(&page_two))
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use crate::config::settings::{FixtureMode, HttpFixtureConfig};
+use crate::utils::error::{EtlError, Result};
+use reqwest::{Request, Response, ResponseBuilderExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+pub const MODE_ENV: &str = "ETL_HTTP_FIXTURES";
+pub const DIRECTORY_ENV: &str = "ETL_HTTP_FIXTURES_DIR";
+const DEFAULT_DIRECTORY: &str = "tests/fixtures/http";
+
+/// 一組錄製的請求與回應，回應內容另存為 `<key>.body`
+#[derive(Debug, Serialize, Deserialize)]
+struct Fixture {
+    method: String,
+    url: String,
+    status: u16,
+    /// 轉址後的最終網址
+    response_url: String,
+    headers: Vec<(String, String)>,
+    recorded_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 以 method、網址與 body 為鍵錄製或重播 HTTP 回應；標頭（例如認證資訊）不參與比對
+#[derive(Debug)]
+pub struct HttpFixtures {
+    mode: FixtureMode,
+    directory: PathBuf,
+}
+
+impl HttpFixtures {
+    pub fn new(config: &HttpFixtureConfig) -> Self {
+        Self {
+            mode: config.mode,
+            directory: PathBuf::from(config.directory.as_deref().unwrap_or(DEFAULT_DIRECTORY)),
+        }
+    }
+
+    /// `ETL_HTTP_FIXTURES=record|replay`，未設定或為 `off` 時停用；無法辨識的值只記錄警告並停用
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(MODE_ENV).ok()?;
+        let mode = match Self::parse_mode(&value) {
+            Ok(mode) => mode?,
+            Err(e) => {
+                warn!("{}; HTTP fixtures are disabled", e);
+                return None;
+            }
+        };
+        Some(Self::new(&HttpFixtureConfig {
+            mode,
+            directory: std::env::var(DIRECTORY_ENV).ok(),
+        }))
+    }
+
+    fn parse_mode(value: &str) -> Result<Option<FixtureMode>> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "off" => Ok(None),
+            "record" => Ok(Some(FixtureMode::Record)),
+            "replay" => Ok(Some(FixtureMode::Replay)),
+            other => Err(EtlError::ConfigError(format!(
+                "Invalid {} '{}', expected record, replay or off",
+                MODE_ENV, other
+            ))),
+        }
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    pub fn replay(&self, request: &Request) -> Result<Response> {
+        let key = Self::key(request);
+        let (meta_path, body_path) = self.paths(&key);
+        let missing = |e: std::io::Error| {
+            EtlError::ApiError(format!(
+                "No recorded fixture for {} {} in {} ({}), record it with {}=record",
+                request.method(),
+                request.url(),
+                self.directory.display(),
+                e,
+                MODE_ENV
+            ))
+        };
+        let meta = std::fs::read_to_string(&meta_path).map_err(missing)?;
+        let fixture: Fixture = serde_json::from_str(&meta).map_err(|e| {
+            EtlError::ParseError(format!("Invalid fixture {}: {}", meta_path.display(), e))
+        })?;
+        let body = std::fs::read(&body_path).map_err(missing)?;
+
+        debug!("Replaying {} {} from {}", fixture.method, fixture.url, meta_path.display());
+        Self::build_response(&fixture, body)
+    }
+
+    /// 讀取完整回應寫入 fixture 目錄，回傳內容相同的新回應
+    pub async fn record(&self, request: &Request, response: Response) -> Result<Response> {
+        let fixture = Fixture {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            status: response.status().as_u16(),
+            response_url: response.url().to_string(),
+            headers: response
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+                .collect(),
+            recorded_at: chrono::Utc::now(),
+        };
+        let body = response.bytes().await?.to_vec();
+
+        let key = Self::key(request);
+        let (meta_path, body_path) = self.paths(&key);
+        std::fs::create_dir_all(&self.directory)?;
+        Self::write_atomic(&body_path, &body)?;
+        Self::write_atomic(&meta_path, serde_json::to_string_pretty(&fixture)?.as_bytes())?;
+        debug!("Recorded {} {} to {}", fixture.method, fixture.url, meta_path.display());
+
+        Self::build_response(&fixture, body)
+    }
+
+    fn key(request: &Request) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(request.method().as_str().as_bytes());
+        hasher.update(b" ");
+        hasher.update(request.url().as_str().as_bytes());
+        if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+            hasher.update(b"\n");
+            hasher.update(body);
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    fn build_response(fixture: &Fixture, body: Vec<u8>) -> Result<Response> {
+        let url = reqwest::Url::parse(&fixture.response_url).map_err(|e| {
+            EtlError::ParseError(format!("Invalid fixture url {}: {}", fixture.response_url, e))
+        })?;
+        let mut builder = http::Response::builder().status(fixture.status).url(url);
+        for (name, value) in &fixture.headers {
+            builder = builder.header(name, value);
+        }
+        let response = builder.body(body).map_err(|e| {
+            EtlError::ParseError(format!("Invalid fixture for {}: {}", fixture.url, e))
+        })?;
+        Ok(Response::from(response))
+    }
+
+    fn paths(&self, key: &str) -> (PathBuf, PathBuf) {
+        (
+            self.directory.join(format!("{}.json", key)),
+            self.directory.join(format!("{}.body", key)),
+        )
+    }
+
+    fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::ApiSourceConfig;
+    use crate::extractors::api_client::ApiClient;
+    use crate::test_support::{TestResponse, TestServer};
+
+    fn fixtures(mode: FixtureMode, directory: &Path) -> HttpFixtureConfig {
+        HttpFixtureConfig { mode, directory: Some(directory.to_string_lossy().to_string()) }
+    }
+
+    #[test]
+    fn invalid_mode_is_rejected() {
+        assert_eq!(HttpFixtures::parse_mode(" Replay ").unwrap(), Some(FixtureMode::Replay));
+        assert_eq!(HttpFixtures::parse_mode("off").unwrap(), None);
+        assert!(HttpFixtures::parse_mode("replya").is_err());
+    }
+
+    /// 使用儲存庫中錄製的回應，不需要網路
+    #[tokio::test]
+    async fn replays_committed_recording() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_DIRECTORY);
+        let source: ApiSourceConfig = serde_json::from_value(serde_json::json!({
+            "url": "https://api.example.com/v1/products",
+            "query_params": { "page": "1" },
+            "records_path": "$.data",
+        }))
+        .unwrap();
+
+        let client = ApiClient::new().with_fixtures(&fixtures(FixtureMode::Replay, &directory));
+        let records = client.fetch_source(&source).await.unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["name"], "Widget");
+        assert_eq!(records[1]["price"], 12.0);
+    }
+
+    #[tokio::test]
+    async fn recorded_responses_replay_without_server() {
+        let server = TestServer::start(|_| {
+            TestResponse::json(200, serde_json::json!({ "items": [{ "id": 7 }] })).header("ETag", "\"v1\"")
+        })
+        .await;
+        let directory = tempfile::tempdir().unwrap();
+        let url = format!("{}/items", server.url);
+
+        let recorder = ApiClient::new().with_fixtures(&fixtures(FixtureMode::Record, directory.path()));
+        let recorded = recorder.fetch_json(&url, None, None, None, None).await.unwrap();
+
+        let replayer = ApiClient::new().with_fixtures(&fixtures(FixtureMode::Replay, directory.path()));
+        let response = replayer.fetch_with_config(&url, None, None, None, None).await.unwrap();
+        assert_eq!(response.headers()["etag"], "\"v1\"");
+        assert_eq!(response.json::<serde_json::Value>().await.unwrap(), recorded);
+        assert_eq!(server.requests().len(), 1);
+
+        let missing = replayer.fetch_json(&format!("{}/other", server.url), None, None, None, None).await;
+        assert!(matches!(missing, Err(EtlError::ApiError(_))));
+    }
+}
@@ -1,3 +1,15 @@
 pub mod api_client;
 pub mod file_reader;
+pub mod graphql;
+pub mod http_cache;
+pub mod http_fixtures;
+pub mod oauth2;
+pub mod payload;
+pub mod rate_limiter;
+pub mod retry;
+pub mod s3;
+pub mod session;
+pub mod signing;
+pub mod sqlite;
+pub mod surreal;
 
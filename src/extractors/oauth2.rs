@@ -0,0 +1,254 @@
+use crate::config::settings::AuthCredentials;
+use crate::utils::error::{EtlError, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::debug;
+
+/// token 到期前多久主動更新
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+    refresh_token: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Option<Instant>,
+    refresh_token: Option<String>,
+}
+
+impl CachedToken {
+    fn is_fresh(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => Instant::now() + REFRESH_MARGIN < expires_at,
+            None => true,
+        }
+    }
+}
+
+/// 取得並快取 OAuth2 access token，支援 client credentials 與 refresh token 兩種 grant
+#[derive(Default)]
+pub struct OAuth2TokenProvider {
+    tokens: Mutex<HashMap<String, CachedToken>>,
+}
+
+impl OAuth2TokenProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 回傳有效的 access token，快取的 token 即將到期時會先更新
+    pub async fn access_token(&self, client: &Client, credentials: &AuthCredentials) -> Result<String> {
+        let key = Self::cache_key(credentials)?;
+        // 持有鎖直到取得新 token，避免並行請求重複向授權伺服器要 token
+        let mut tokens = self.tokens.lock().await;
+
+        if let Some(cached) = tokens.get(&key).filter(|token| token.is_fresh()) {
+            return Ok(cached.access_token.clone());
+        }
+
+        let refresh_token = tokens
+            .get(&key)
+            .and_then(|token| token.refresh_token.clone())
+            .or_else(|| credentials.refresh_token.clone());
+        let token = self.request_token(client, credentials, refresh_token).await?;
+        let access_token = token.access_token.clone();
+        tokens.insert(key, token);
+        Ok(access_token)
+    }
+
+    /// 收到 401 時捨棄快取的 access token，下次呼叫會重新取得
+    pub async fn invalidate(&self, credentials: &AuthCredentials) {
+        if let Ok(key) = Self::cache_key(credentials) {
+            if let Some(token) = self.tokens.lock().await.get_mut(&key) {
+                token.expires_at = Some(Instant::now());
+            }
+        }
+    }
+
+    async fn request_token(
+        &self,
+        client: &Client,
+        credentials: &AuthCredentials,
+        refresh_token: Option<String>,
+    ) -> Result<CachedToken> {
+        let token_url = Self::require(&credentials.token_url, "token_url")?;
+        let client_id = Self::require(&credentials.client_id, "client_id")?;
+
+        let mut form: Vec<(&str, String)> = vec![("client_id", client_id.clone())];
+        if let Some(secret) = &credentials.client_secret {
+            form.push(("client_secret", secret.clone()));
+        }
+        match &refresh_token {
+            Some(refresh_token) => {
+                form.push(("grant_type", "refresh_token".to_string()));
+                form.push(("refresh_token", refresh_token.clone()));
+            }
+            None => form.push(("grant_type", "client_credentials".to_string())),
+        }
+        if let Some(scopes) = credentials.scopes.as_ref().filter(|s| !s.is_empty()) {
+            form.push(("scope", scopes.join(" ")));
+        }
+
+        debug!(
+            "Requesting OAuth2 token from {} ({})",
+            token_url,
+            if refresh_token.is_some() { "refresh_token" } else { "client_credentials" }
+        );
+        let response = client.post(token_url).form(&form).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(EtlError::AuthError(format!(
+                "OAuth2 token request failed with {}: {}",
+                status, body
+            )));
+        }
+
+        let token: TokenResponse = response.json().await.map_err(|e| {
+            EtlError::AuthError(format!("Invalid OAuth2 token response: {}", e))
+        })?;
+
+        Ok(CachedToken {
+            access_token: token.access_token,
+            expires_at: token.expires_in.map(|secs| Instant::now() + Duration::from_secs(secs)),
+            // 授權伺服器未輪替 refresh token 時沿用原本的
+            refresh_token: token.refresh_token.or(refresh_token),
+        })
+    }
+
+    fn cache_key(credentials: &AuthCredentials) -> Result<String> {
+        let token_url = Self::require(&credentials.token_url, "token_url")?;
+        let client_id = Self::require(&credentials.client_id, "client_id")?;
+        let scopes = credentials.scopes.as_deref().unwrap_or_default().join(" ");
+        Ok(format!("{}|{}|{}", token_url, client_id, scopes))
+    }
+
+    fn require<'a>(value: &'a Option<String>, name: &str) -> Result<&'a String> {
+        value
+            .as_ref()
+            .ok_or_else(|| EtlError::ConfigError(format!("OAuth2 auth requires {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::{AuthConfig, AuthType};
+    use crate::extractors::api_client::ApiClient;
+    use crate::test_support::{TestResponse, TestServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// `/token` 每次核發新的 token（`t-1`、`t-2`…）與對應的 refresh token
+    async fn token_server(expires_in: u64) -> TestServer {
+        let issued = AtomicUsize::new(0);
+        TestServer::start(move |request| match request.path() {
+            "/token" => {
+                let n = issued.fetch_add(1, Ordering::SeqCst) + 1;
+                TestResponse::json(
+                    200,
+                    serde_json::json!({
+                        "access_token": format!("t-{}", n),
+                        "token_type": "Bearer",
+                        "expires_in": expires_in,
+                        "refresh_token": format!("r-{}", n)
+                    }),
+                )
+            }
+            // 模擬 t-1 在到期前被撤銷
+            _ if request.header("authorization") == Some("Bearer t-1") => TestResponse::new(401, "revoked"),
+            _ => TestResponse::json(200, serde_json::json!([{ "id": 1 }])),
+        })
+        .await
+    }
+
+    fn credentials(server: &TestServer, refresh_token: Option<&str>) -> AuthCredentials {
+        AuthCredentials {
+            token_url: Some(format!("{}/token", server.url)),
+            client_id: Some("etl".to_string()),
+            client_secret: Some("s3cr3t".to_string()),
+            scopes: Some(vec!["read".to_string(), "write".to_string()]),
+            refresh_token: refresh_token.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn token_requests(server: &TestServer) -> Vec<String> {
+        server.requests().iter().filter(|r| r.path() == "/token").map(|r| r.body_text()).collect()
+    }
+
+    #[tokio::test]
+    async fn client_credentials_token_is_cached_until_expiry() {
+        let server = token_server(3600).await;
+        let provider = OAuth2TokenProvider::new();
+        let credentials = credentials(&server, None);
+        let client = Client::new();
+
+        assert_eq!(provider.access_token(&client, &credentials).await.unwrap(), "t-1");
+        assert_eq!(provider.access_token(&client, &credentials).await.unwrap(), "t-1");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].header("content-type"), Some("application/x-www-form-urlencoded"));
+        assert_eq!(
+            requests[0].body_text(),
+            "client_id=etl&client_secret=s3cr3t&grant_type=client_credentials&scope=read+write"
+        );
+    }
+
+    #[tokio::test]
+    async fn token_close_to_expiry_is_refreshed() {
+        // 剩餘時間少於 REFRESH_MARGIN，下一次呼叫就會更新
+        let server = token_server(30).await;
+        let provider = OAuth2TokenProvider::new();
+        let credentials = credentials(&server, None);
+        let client = Client::new();
+
+        assert_eq!(provider.access_token(&client, &credentials).await.unwrap(), "t-1");
+        assert_eq!(provider.access_token(&client, &credentials).await.unwrap(), "t-2");
+
+        let requests = token_requests(&server);
+        assert!(requests[1].contains("grant_type=refresh_token&refresh_token=r-1"), "{}", requests[1]);
+    }
+
+    #[tokio::test]
+    async fn configured_refresh_token_uses_refresh_grant() {
+        let server = token_server(3600).await;
+        let provider = OAuth2TokenProvider::new();
+
+        let token = provider.access_token(&Client::new(), &credentials(&server, Some("initial"))).await.unwrap();
+
+        assert_eq!(token, "t-1");
+        assert!(token_requests(&server)[0].contains("grant_type=refresh_token&refresh_token=initial"));
+    }
+
+    #[tokio::test]
+    async fn unauthorized_response_refreshes_token_and_retries() {
+        let server = token_server(3600).await;
+        let auth = AuthConfig { auth_type: AuthType::OAuth2, credentials: credentials(&server, None) };
+
+        let body = ApiClient::new()
+            .fetch_json(&format!("{}/items", server.url), None, None, Some(auth), None)
+            .await
+            .unwrap();
+
+        assert_eq!(body, serde_json::json!([{ "id": 1 }]));
+        let requests = server.requests();
+        let summary: Vec<(&str, Option<&str>)> =
+            requests.iter().map(|r| (r.path(), r.header("authorization"))).collect();
+        assert_eq!(
+            summary,
+            [("/token", None), ("/items", Some("Bearer t-1")), ("/token", None), ("/items", Some("Bearer t-2"))]
+        );
+        assert!(requests[2].body_text().contains("grant_type=refresh_token&refresh_token=r-1"));
+    }
+}
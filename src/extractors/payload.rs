@@ -0,0 +1,653 @@
+use crate::config::settings::ResponseFormat;
+use crate::extractors::file_reader::FileReader;
+use crate::models::data_types::DataRecord;
+use crate::utils::error::{EtlError, Result};
+use crate::utils::helpers::extract_records;
+use flate2::read::GzDecoder;
+use futures::StreamExt;
+use quick_xml::encoding::Decoder;
+use quick_xml::events::{BytesStart, Event};
+use reqwest::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
+use reqwest::Response;
+use std::collections::HashMap;
+use serde::de::IgnoredAny;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use tempfile::NamedTempFile;
+use tracing::{debug, info};
+
+/// 判斷格式時最多檢查的開頭位元組數
+const SNIFF_LEN: usize = 8 * 1024;
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const CSV_DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PayloadFormat {
+    Json,
+    Ndjson,
+    Csv,
+    Xml,
+    Zip,
+    Gzip,
+}
+
+impl PayloadFormat {
+    /// 依序以 magic bytes（zip、gzip）、`Content-Type`、檔名與內容開頭判斷
+    pub fn detect(content_type: Option<&str>, filename: Option<&str>, head: &[u8]) -> Option<Self> {
+        Self::declared(content_type, filename, head).or_else(|| Self::sniff(head))
+    }
+
+    /// magic bytes、`Content-Type` 或檔名明確指出的格式
+    fn declared(content_type: Option<&str>, filename: Option<&str>, head: &[u8]) -> Option<Self> {
+        Self::from_magic(head)
+            .or_else(|| content_type.and_then(Self::from_content_type))
+            .or_else(|| filename.and_then(Self::from_filename))
+    }
+
+    fn from_magic(head: &[u8]) -> Option<Self> {
+        if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+            Some(Self::Zip)
+        } else if head.starts_with(b"\x1f\x8b") {
+            Some(Self::Gzip)
+        } else {
+            None
+        }
+    }
+
+    fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_lowercase();
+        match mime.as_str() {
+            "application/x-ndjson" | "application/ndjson" | "application/jsonl"
+            | "application/x-jsonlines" => Some(Self::Ndjson),
+            "text/csv" | "application/csv" | "text/tab-separated-values" => Some(Self::Csv),
+            "application/zip" | "application/x-zip-compressed" => Some(Self::Zip),
+            "application/gzip" | "application/x-gzip" => Some(Self::Gzip),
+            "application/json" | "text/json" => Some(Self::Json),
+            "application/xml" | "text/xml" => Some(Self::Xml),
+            _ if mime.ends_with("+json") => Some(Self::Json),
+            _ if mime.ends_with("+xml") => Some(Self::Xml),
+            _ => None,
+        }
+    }
+
+    fn from_filename(filename: &str) -> Option<Self> {
+        let extension = filename.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(Self::Json),
+            "ndjson" | "jsonl" => Some(Self::Ndjson),
+            "csv" | "tsv" => Some(Self::Csv),
+            "xml" => Some(Self::Xml),
+            "zip" => Some(Self::Zip),
+            "gz" => Some(Self::Gzip),
+            _ => None,
+        }
+    }
+
+    /// 文字內容：`<` 為 XML，`[` 為 JSON，`{` 開頭且第二行也是物件時為 NDJSON，其餘文字視為 CSV；
+    /// 含控制字元的二進位內容回傳 None
+    fn sniff(head: &[u8]) -> Option<Self> {
+        let text = Self::text_start(head);
+        match text.first()? {
+            b'<' => Some(Self::Xml),
+            b'[' => Some(Self::Json),
+            b'{' => {
+                let mut lines = text.split(|b| *b == b'\n').map(<[u8]>::trim_ascii);
+                let first = lines.next().unwrap_or_default();
+                let is_ndjson = serde_json::from_slice::<serde_json::Value>(first).is_ok()
+                    && lines.find(|line| !line.is_empty()).is_some_and(|line| line.starts_with(b"{"));
+                Some(if is_ndjson { Self::Ndjson } else { Self::Json })
+            }
+            _ if Self::is_text(text) => Some(Self::Csv),
+            _ => None,
+        }
+    }
+
+    fn text_start(head: &[u8]) -> &[u8] {
+        head.strip_prefix(UTF8_BOM).unwrap_or(head).trim_ascii_start()
+    }
+
+    /// 不檢查編碼，只排除 tab 與換行以外的控制字元，讓 Big5 等非 UTF-8 的 CSV 仍可辨識
+    fn is_text(bytes: &[u8]) -> bool {
+        bytes.iter().all(|b| *b >= 0x20 || matches!(b, b'\t' | b'\n' | b'\r'))
+    }
+
+    /// `{` 開頭但開頭位元組內沒有換行時，第一行被截斷，無法判斷是否為 NDJSON
+    fn first_line_truncated(head: &[u8]) -> bool {
+        let text = Self::text_start(head);
+        text.starts_with(b"{") && !text.contains(&b'\n')
+    }
+
+    /// 讀完第一個 JSON 值（不保留內容），之後還有其他值時為 NDJSON
+    fn json_or_ndjson<R: Read>(reader: R) -> Self {
+        let mut values =
+            serde_json::Deserializer::from_reader(BufReader::new(reader)).into_iter::<IgnoredAny>();
+        if matches!(values.next(), Some(Ok(_))) && values.next().is_some() {
+            Self::Ndjson
+        } else {
+            Self::Json
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Ndjson => "NDJSON",
+            Self::Csv => "CSV",
+            Self::Xml => "XML",
+            Self::Zip => "zip",
+            Self::Gzip => "gzip",
+        }
+    }
+
+    fn from_requested(format: ResponseFormat) -> Option<Self> {
+        match format {
+            ResponseFormat::Auto => None,
+            ResponseFormat::Json => Some(Self::Json),
+            ResponseFormat::Ndjson => Some(Self::Ndjson),
+            ResponseFormat::Csv => Some(Self::Csv),
+            ResponseFormat::Xml => Some(Self::Xml),
+            ResponseFormat::Zip => Some(Self::Zip),
+            ResponseFormat::Gzip => Some(Self::Gzip),
+        }
+    }
+}
+
+/// 串流寫入暫存檔的 API 回應，檔案在 drop 時刪除
+pub struct Download {
+    url: String,
+    file: NamedTempFile,
+    content_type: Option<String>,
+    filename: Option<String>,
+    size: u64,
+}
+
+impl Download {
+    pub async fn from_response(response: Response) -> Result<Self> {
+        let url = response.url().to_string();
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let content_type = header(CONTENT_TYPE);
+        let filename = header(CONTENT_DISPOSITION).as_deref().and_then(Self::disposition_filename);
+
+        let mut file = tempfile::Builder::new().prefix("etl-download-").tempfile()?;
+        let mut size = 0;
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk)?;
+            size += chunk.len() as u64;
+        }
+        file.flush()?;
+        debug!("Downloaded {} bytes from {} to {}", size, url, file.path().display());
+
+        Ok(Self { url, file, content_type, filename, size })
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn path(&self) -> &Path {
+        self.file.path()
+    }
+
+    /// 解析為 JSON 記錄；`records_path` 與 `envelope_fields` 適用於 JSON 與 XML
+    pub fn parse(
+        &self,
+        format: ResponseFormat,
+        records_path: Option<&str>,
+        envelope_fields: Option<&HashMap<String, String>>,
+    ) -> Result<Vec<serde_json::Value>> {
+        if self.size == 0 {
+            return Err(EtlError::ParseError(format!("Empty response from {}", self.url)));
+        }
+
+        let mut reader = BufReader::with_capacity(SNIFF_LEN, self.file.reopen()?);
+        let detected = match PayloadFormat::from_requested(format) {
+            Some(format) => format,
+            None => {
+                let head = reader.fill_buf()?;
+                self.detect(self.content_type.as_deref(), self.filename.as_deref(), head, || {
+                    Ok(self.file.reopen()?)
+                })?
+            }
+        };
+        info!("Parsing {} response from {} ({} bytes)", detected.name(), self.url, self.size);
+
+        match detected {
+            PayloadFormat::Zip => {
+                let records = FileReader::new().parse_zip(reader, &[], None)?;
+                Ok(records.into_iter().map(record_value).collect())
+            }
+            PayloadFormat::Gzip => {
+                // 內層格式由去掉 .gz 的檔名或解壓後的內容判斷
+                let mut reader = BufReader::with_capacity(SNIFF_LEN, GzDecoder::new(reader));
+                let inner_name = self.filename.as_deref().and_then(|name| name.strip_suffix(".gz"));
+                let head = reader.fill_buf()?;
+                match self.detect(None, inner_name, head, || Ok(GzDecoder::new(self.file.reopen()?)))? {
+                    PayloadFormat::Zip | PayloadFormat::Gzip => Err(EtlError::ParseError(format!(
+                        "Nested archives are not supported in gzip response from {}",
+                        self.url
+                    ))),
+                    inner => self.parse_text(inner, reader, records_path, envelope_fields),
+                }
+            }
+            text => self.parse_text(text, reader, records_path, envelope_fields),
+        }
+    }
+
+    /// 同 `PayloadFormat::detect`，但第一行超過 `SNIFF_LEN` 時以 `reread` 從頭讀完第一筆值再區分 JSON 與 NDJSON
+    fn detect<R: Read>(
+        &self,
+        content_type: Option<&str>,
+        filename: Option<&str>,
+        head: &[u8],
+        reread: impl FnOnce() -> Result<R>,
+    ) -> Result<PayloadFormat> {
+        if let Some(format) = PayloadFormat::declared(content_type, filename, head) {
+            return Ok(format);
+        }
+        if PayloadFormat::first_line_truncated(head) {
+            return Ok(PayloadFormat::json_or_ndjson(reread()?));
+        }
+        PayloadFormat::sniff(head).ok_or_else(|| self.unknown_format())
+    }
+
+    fn parse_text<R: BufRead>(
+        &self,
+        format: PayloadFormat,
+        mut reader: R,
+        records_path: Option<&str>,
+        envelope_fields: Option<&HashMap<String, String>>,
+    ) -> Result<Vec<serde_json::Value>> {
+        if reader.fill_buf()?.starts_with(UTF8_BOM) {
+            reader.consume(UTF8_BOM.len());
+        }
+        let invalid = |what: &str, e: &dyn std::fmt::Display| {
+            EtlError::ParseError(format!("Invalid {} response from {}: {}", what, self.url, e))
+        };
+
+        match format {
+            PayloadFormat::Json => {
+                let body: serde_json::Value =
+                    serde_json::from_reader(reader).map_err(|e| invalid("JSON", &e))?;
+                extract_records(body, records_path, envelope_fields)
+            }
+            PayloadFormat::Ndjson => {
+                let mut records = Vec::new();
+                for (index, line) in reader.lines().enumerate() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let record = serde_json::from_str(&line)
+                        .map_err(|e| invalid("NDJSON", &format!("line {}: {}", index + 1, e)))?;
+                    records.push(record);
+                }
+                Ok(records)
+            }
+            PayloadFormat::Csv => {
+                let delimiter = Self::csv_delimiter(reader.fill_buf()?);
+                let records = FileReader::new().parse_csv(reader, delimiter as char, true)?;
+                Ok(records.into_iter().map(record_value).collect())
+            }
+            PayloadFormat::Xml => {
+                let document = xml_to_json(reader).map_err(|e| invalid("XML", &e))?;
+                match records_path {
+                    Some(_) => extract_records(document, records_path, envelope_fields),
+                    None => Ok(default_xml_records(document)),
+                }
+            }
+            PayloadFormat::Zip | PayloadFormat::Gzip => Err(self.unknown_format()),
+        }
+    }
+
+    /// 第一行出現最多次的分隔符號，預設逗號
+    fn csv_delimiter(head: &[u8]) -> u8 {
+        let first_line = head.split(|b| *b == b'\n').next().unwrap_or_default();
+        CSV_DELIMITERS
+            .iter()
+            .copied()
+            .max_by_key(|delimiter| first_line.iter().filter(|b| *b == delimiter).count())
+            .filter(|delimiter| first_line.contains(delimiter))
+            .unwrap_or(b',')
+    }
+
+    /// `attachment; filename="export.csv"` 或 `filename*=UTF-8''export.csv`
+    fn disposition_filename(value: &str) -> Option<String> {
+        let params: Vec<(&str, &str)> = value
+            .split(';')
+            .filter_map(|param| param.trim().split_once('='))
+            .map(|(name, value)| (name.trim(), value.trim()))
+            .collect();
+        params
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("filename*"))
+            .map(|(_, value)| value.rsplit('\'').next().unwrap_or(value))
+            .or_else(|| {
+                params
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case("filename"))
+                    .map(|(_, value)| value.trim_matches('"'))
+            })
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+    }
+
+    fn unknown_format(&self) -> EtlError {
+        EtlError::ParseError(format!(
+            "Could not detect the response format of {} (Content-Type: {})",
+            self.url,
+            self.content_type.as_deref().unwrap_or("none")
+        ))
+    }
+}
+
+fn record_value(record: DataRecord) -> serde_json::Value {
+    serde_json::Value::Object(record.fields.into_iter().collect())
+}
+
+/// 解析中的 XML 元素
+struct XmlElement {
+    name: String,
+    fields: serde_json::Map<String, serde_json::Value>,
+    text: String,
+}
+
+impl XmlElement {
+    fn start(element: &BytesStart, decoder: Decoder) -> quick_xml::Result<Self> {
+        let mut fields = serde_json::Map::new();
+        for attribute in element.attributes() {
+            let attribute = attribute?;
+            fields.insert(
+                format!("@{}", String::from_utf8_lossy(attribute.key.as_ref())),
+                serde_json::Value::String(attribute.decode_and_unescape_value(decoder)?.into_owned()),
+            );
+        }
+        Ok(Self {
+            name: String::from_utf8_lossy(element.name().as_ref()).into_owned(),
+            fields,
+            text: String::new(),
+        })
+    }
+
+    /// 只有文字的元素轉為字串，有屬性或子元素時文字放在 `#text`
+    fn into_value(mut self) -> serde_json::Value {
+        let text = self.text.trim();
+        match (self.fields.is_empty(), text.is_empty()) {
+            (true, true) => serde_json::Value::Null,
+            (true, false) => serde_json::Value::String(text.to_string()),
+            (false, true) => serde_json::Value::Object(self.fields),
+            (false, false) => {
+                self.fields.insert("#text".to_string(), serde_json::Value::String(text.to_string()));
+                serde_json::Value::Object(self.fields)
+            }
+        }
+    }
+}
+
+/// 將 XML 文件轉為 `{ "<root>": { ... } }`，屬性以 `@` 開頭，重複的子元素轉為陣列
+pub(crate) fn xml_to_json<R: BufRead>(reader: R) -> quick_xml::Result<serde_json::Value> {
+    let mut reader = quick_xml::Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut stack: Vec<XmlElement> = Vec::new();
+    let mut root = serde_json::Map::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(element) => stack.push(XmlElement::start(&element, reader.decoder())?),
+            Event::Empty(element) => {
+                close_element(XmlElement::start(&element, reader.decoder())?, &mut stack, &mut root)
+            }
+            Event::End(_) => {
+                if let Some(element) = stack.pop() {
+                    close_element(element, &mut stack, &mut root);
+                }
+            }
+            Event::Text(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text.decode()?);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&data.decode()?);
+                }
+            }
+            Event::GeneralRef(reference) => {
+                if let Some(element) = stack.last_mut() {
+                    match reference.resolve_char_ref()? {
+                        Some(c) => element.text.push(c),
+                        None => {
+                            let name = reference.decode()?;
+                            match quick_xml::escape::resolve_predefined_entity(&name) {
+                                Some(resolved) => element.text.push_str(resolved),
+                                None => element.text.push_str(&format!("&{};", name)),
+                            }
+                        }
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(serde_json::Value::Object(root))
+}
+
+fn close_element(
+    element: XmlElement,
+    stack: &mut [XmlElement],
+    root: &mut serde_json::Map<String, serde_json::Value>,
+) {
+    let name = element.name.clone();
+    let value = element.into_value();
+    let fields = match stack.last_mut() {
+        Some(parent) => &mut parent.fields,
+        None => root,
+    };
+    // 元素的值不會是陣列，已存在的陣列代表重複的子元素
+    match fields.remove(&name) {
+        Some(serde_json::Value::Array(mut items)) => {
+            items.push(value);
+            fields.insert(name, serde_json::Value::Array(items));
+        }
+        Some(existing) => {
+            fields.insert(name, serde_json::Value::Array(vec![existing, value]));
+        }
+        None => {
+            fields.insert(name, value);
+        }
+    }
+}
+
+/// 未指定 `records_path` 時，根元素只有一種子元素就以其為記錄，否則整份文件為一筆記錄
+fn default_xml_records(document: serde_json::Value) -> Vec<serde_json::Value> {
+    let serde_json::Value::Object(document) = document else {
+        return vec![document];
+    };
+    let Some((_, root)) = document.into_iter().next() else {
+        return Vec::new();
+    };
+    match root {
+        serde_json::Value::Object(mut fields) => {
+            let children: Vec<&String> = fields
+                .keys()
+                .filter(|key| !key.starts_with('@') && *key != "#text")
+                .collect();
+            if children.len() != 1 {
+                return vec![serde_json::Value::Object(fields)];
+            }
+            let child = children[0].clone();
+            match fields.remove(&child) {
+                Some(serde_json::Value::Array(items)) => items,
+                Some(serde_json::Value::Null) | None => Vec::new(),
+                Some(item) => vec![item],
+            }
+        }
+        serde_json::Value::Null => Vec::new(),
+        other => vec![other],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    fn download(content_type: Option<&str>, filename: Option<&str>, body: &[u8]) -> Download {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(body).unwrap();
+        Download {
+            url: "http://localhost/export".to_string(),
+            file,
+            content_type: content_type.map(str::to_string),
+            filename: filename.map(str::to_string),
+            size: body.len() as u64,
+        }
+    }
+
+    fn gzip(body: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(body).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn parse_auto(download: &Download) -> Result<Vec<serde_json::Value>> {
+        download.parse(ResponseFormat::Auto, None, None)
+    }
+
+    #[test]
+    fn detect_prefers_magic_bytes_then_content_type_then_filename() {
+        use PayloadFormat::*;
+        let cases = [
+            (Some("application/json"), None, &b"PK\x03\x04rest"[..], Some(Zip)),
+            (Some("text/csv"), Some("a.csv"), b"\x1f\x8b\x08", Some(Gzip)),
+            (Some("application/x-ndjson; charset=utf-8"), Some("a.json"), b"{}", Some(Ndjson)),
+            (Some("application/vnd.api+json"), None, b"a,b", Some(Json)),
+            (Some("application/atom+xml"), None, b"{}", Some(Xml)),
+            (Some("text/tab-separated-values"), None, b"{}", Some(Csv)),
+            (Some("application/octet-stream"), Some("export.JSONL"), b"[]", Some(Ndjson)),
+            (None, Some("export.tsv"), b"[]", Some(Csv)),
+            (None, Some("export.csv.gz"), b"id,name", Some(Gzip)),
+            (None, Some("export"), b"\xEF\xBB\xBF  <items/>", Some(Xml)),
+            (None, None, b"\n[{\"id\": 1}]", Some(Json)),
+            (None, None, b"id;name\n1;a\n", Some(Csv)),
+        ];
+
+        for (content_type, filename, head, expected) in cases {
+            let detected = PayloadFormat::detect(content_type, filename, head);
+            assert_eq!(detected, expected, "{:?} {:?}", content_type, filename);
+        }
+    }
+
+    #[test]
+    fn sniff_tells_json_from_ndjson_and_rejects_binary() {
+        assert_eq!(PayloadFormat::sniff(b"{\"id\": 1}\n{\"id\": 2}\n"), Some(PayloadFormat::Ndjson));
+        assert_eq!(PayloadFormat::sniff(b"{\n  \"items\": []\n}\n"), Some(PayloadFormat::Json));
+        assert_eq!(PayloadFormat::sniff(b"{\"id\": 1}\n\n"), Some(PayloadFormat::Json));
+        assert_eq!(PayloadFormat::sniff("編號,名稱\n1,甲\n".as_bytes()), Some(PayloadFormat::Csv));
+        assert_eq!(PayloadFormat::sniff(b"\x89PNG\r\n\x1a\n\x00\x00"), None);
+        assert_eq!(PayloadFormat::sniff(b"\x00\x01\x02\x03"), None);
+        assert_eq!(PayloadFormat::sniff(b"  \n\t"), None);
+    }
+
+    #[test]
+    fn unknown_binary_payload_is_a_parse_error() {
+        let error = parse_auto(&download(Some("application/octet-stream"), None, b"\x00\x01binary")).unwrap_err();
+        assert!(error.to_string().contains("Could not detect the response format"), "{}", error);
+    }
+
+    #[test]
+    fn ndjson_with_a_first_line_longer_than_the_sniff_buffer() {
+        let long = "x".repeat(SNIFF_LEN * 2);
+        let body = format!("{{\"id\": 1, \"note\": \"{}\"}}\n{{\"id\": 2, \"note\": \"short\"}}\n", long);
+        let records = parse_auto(&download(None, None, body.as_bytes())).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["note"], "short");
+
+        let single = format!("{{\"id\": 1, \"note\": \"{}\"}}", long);
+        let records = parse_auto(&download(None, None, single.as_bytes())).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["id"], 1);
+
+        let gzipped = parse_auto(&download(None, Some("export.gz"), &gzip(body.as_bytes()))).unwrap();
+        assert_eq!(gzipped.len(), 2);
+    }
+
+    #[test]
+    fn gzip_inner_format_comes_from_the_filename_or_content() {
+        let csv = gzip(b"id;name\n1;a\n2;b\n");
+        let records = parse_auto(&download(Some("application/gzip"), Some("export.csv.gz"), &csv)).unwrap();
+        assert_eq!(records[1], serde_json::json!({ "id": 2, "name": "b" }));
+
+        let ndjson = gzip(b"{\"id\": 1}\n{\"id\": 2}\n");
+        assert_eq!(parse_auto(&download(None, None, &ndjson)).unwrap().len(), 2);
+
+        let nested = gzip(&gzip(b"[]"));
+        let error = parse_auto(&download(None, Some("export.json.gz.gz"), &nested)).unwrap_err();
+        assert!(error.to_string().contains("Nested archives"), "{}", error);
+
+        let binary = gzip(b"\x00\x01\x02");
+        let error = parse_auto(&download(None, None, &binary)).unwrap_err();
+        assert!(error.to_string().contains("Could not detect"), "{}", error);
+    }
+
+    #[test]
+    fn disposition_filename_prefers_the_extended_parameter() {
+        let cases = [
+            ("attachment; filename=\"export.csv\"", Some("export.csv")),
+            ("attachment; filename=export.json", Some("export.json")),
+            ("attachment; filename*=UTF-8''export.csv.gz; filename=\"fallback.csv\"", Some("export.csv.gz")),
+            ("attachment; FILENAME*=utf-8'en'report.xml", Some("report.xml")),
+            ("attachment; filename=\"\"", None),
+            ("inline", None),
+        ];
+
+        for (header, expected) in cases {
+            assert_eq!(Download::disposition_filename(header).as_deref(), expected, "{}", header);
+        }
+    }
+
+    #[test]
+    fn csv_delimiter_is_the_most_frequent_on_the_first_line() {
+        assert_eq!(Download::csv_delimiter(b"a;b;c\n1,5;2;3\n"), b';');
+        assert_eq!(Download::csv_delimiter(b"a\tb\tc\n"), b'\t');
+        assert_eq!(Download::csv_delimiter(b"a|b\n"), b'|');
+        assert_eq!(Download::csv_delimiter(b"a,b\n"), b',');
+        assert_eq!(Download::csv_delimiter(b"single\n"), b',');
+    }
+
+    #[test]
+    fn xml_to_json_maps_attributes_text_and_repeated_children() {
+        let xml = r#"<?xml version="1.0"?>
+            <items count="2">
+                <item id="1"><name>A &amp; B</name><tag>x</tag><tag>y</tag></item>
+                <item id="2"><name><![CDATA[<C>]]></name><price currency="TWD">10</price><empty/></item>
+            </items>"#;
+
+        let document = xml_to_json(xml.as_bytes()).unwrap();
+
+        assert_eq!(
+            document,
+            serde_json::json!({
+                "items": {
+                    "@count": "2",
+                    "item": [
+                        { "@id": "1", "name": "A & B", "tag": ["x", "y"] },
+                        { "@id": "2", "name": "<C>", "price": { "@currency": "TWD", "#text": "10" }, "empty": null }
+                    ]
+                }
+            })
+        );
+        assert_eq!(default_xml_records(document).len(), 2);
+    }
+}
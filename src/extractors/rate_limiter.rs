@@ -0,0 +1,226 @@
+use crate::config::settings::RateLimitConfig;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+
+/// 大於此值的 `X-RateLimit-Reset` 視為 Unix 時間戳，否則視為剩餘秒數
+const EPOCH_THRESHOLD: u64 = 1_000_000_000;
+const DEFAULT_MAX_PAUSE_SECS: u64 = 60;
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+    /// 伺服器回報配額用盡時，暫停到此時間點
+    paused_until: Option<Instant>,
+}
+
+/// token bucket 速率限制與同時請求數上限，同一個 `ApiClient` 的所有請求共用
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: Option<f64>,
+    capacity: f64,
+    respect_headers: bool,
+    max_pause: Duration,
+    state: Mutex<BucketState>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+/// 持有期間佔用一個同時請求名額
+pub struct RateLimitPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        let rate = config.requests_per_second.filter(|r| *r > 0.0);
+        let capacity = config
+            .burst
+            .map(|b| b.max(1) as f64)
+            .unwrap_or_else(|| rate.map(|r| r.ceil().max(1.0)).unwrap_or(1.0));
+
+        Self {
+            rate,
+            capacity,
+            respect_headers: config.respect_headers.unwrap_or(true),
+            max_pause: Duration::from_secs(config.max_pause_secs.unwrap_or(DEFAULT_MAX_PAUSE_SECS)),
+            state: Mutex::new(BucketState {
+                tokens: capacity,
+                last_refill: Instant::now(),
+                paused_until: None,
+            }),
+            in_flight: config.max_in_flight.map(|n| Arc::new(Semaphore::new(n.max(1)))),
+        }
+    }
+
+    /// 等待可以送出下一個請求
+    pub async fn acquire(&self) -> RateLimitPermit {
+        let permit = match &self.in_flight {
+            Some(semaphore) => Some(
+                semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("rate limiter semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        // 持有鎖等待，讓排隊中的請求依序取得 token
+        let mut state = self.state.lock().await;
+
+        if let Some(paused_until) = state.paused_until.take() {
+            if paused_until > Instant::now() {
+                debug!("Rate limit exhausted, waiting {:?}", paused_until - Instant::now());
+                tokio::time::sleep_until(paused_until).await;
+            }
+        }
+
+        if let Some(rate) = self.rate {
+            Self::refill(&mut state, rate, self.capacity);
+            if state.tokens < 1.0 {
+                let wait = Duration::from_secs_f64((1.0 - state.tokens) / rate);
+                tokio::time::sleep(wait).await;
+                Self::refill(&mut state, rate, self.capacity);
+            }
+            state.tokens = (state.tokens - 1.0).max(0.0);
+        }
+
+        RateLimitPermit { _permit: permit }
+    }
+
+    /// 依照 `X-RateLimit-Remaining` / `X-RateLimit-Reset` 標頭調整，配額用盡時暫停到重置時間
+    pub async fn observe(&self, headers: &HeaderMap) {
+        if !self.respect_headers {
+            return;
+        }
+
+        let header_u64 = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .map(|v| v.max(0.0) as u64)
+        };
+
+        let Some(remaining) = header_u64("x-ratelimit-remaining") else {
+            return;
+        };
+        if remaining > 0 {
+            return;
+        }
+
+        let wait = match header_u64("x-ratelimit-reset") {
+            Some(reset) if reset > EPOCH_THRESHOLD => {
+                let now = chrono::Utc::now().timestamp().max(0) as u64;
+                Duration::from_secs(reset.saturating_sub(now))
+            }
+            Some(reset) => Duration::from_secs(reset),
+            None => Duration::from_secs(1),
+        };
+        let wait = if wait > self.max_pause {
+            warn!("X-RateLimit-Reset asks for {:?}, capping the pause at {:?}", wait, self.max_pause);
+            self.max_pause
+        } else {
+            wait
+        };
+
+        warn!("Server reported rate limit exhausted, pausing requests for {:?}", wait);
+        let mut state = self.state.lock().await;
+        let until = Instant::now() + wait;
+        state.paused_until = Some(state.paused_until.map_or(until, |current| current.max(until)));
+    }
+
+    #[cfg(test)]
+    async fn paused_for(&self) -> Option<Duration> {
+        let state = self.state.lock().await;
+        state.paused_until.map(|until| until.saturating_duration_since(Instant::now()))
+    }
+
+    fn refill(state: &mut BucketState, rate: f64, capacity: f64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * rate).min(capacity);
+        state.last_refill = now;
+    }
+}
+
+/// 依主機共用速率限制器，同一次執行中資料源與 enrich 對同一主機的請求共用配額
+#[derive(Debug, Default)]
+pub struct RateLimiters {
+    limiters: std::sync::Mutex<HashMap<String, Arc<RateLimiter>>>,
+}
+
+impl RateLimiters {
+    /// 主機已有限制器時沿用（以第一次註冊的設定為準），否則依 `config` 建立並註冊；
+    /// 兩者皆無時回傳 `None`
+    pub fn for_url(&self, url: &str, config: Option<&RateLimitConfig>) -> Option<Arc<RateLimiter>> {
+        let Some(host) = reqwest::Url::parse(url).ok().and_then(|url| {
+            let host = url.host_str()?.to_lowercase();
+            Some(format!("{}:{}", host, url.port_or_known_default().unwrap_or_default()))
+        }) else {
+            return config.map(|config| Arc::new(RateLimiter::new(config)));
+        };
+
+        let mut limiters = self.limiters.lock().expect("rate limiter registry poisoned");
+        if let Some(limiter) = limiters.get(&host) {
+            return Some(limiter.clone());
+        }
+        let limiter = Arc::new(RateLimiter::new(config?));
+        debug!("Registered rate limiter for {}", host);
+        limiters.insert(host, limiter.clone());
+        Some(limiter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn config(max_pause_secs: Option<u64>) -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_second: Some(10.0),
+            burst: None,
+            max_in_flight: None,
+            respect_headers: None,
+            max_pause_secs,
+        }
+    }
+
+    fn exhausted(reset: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_str(reset).unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn reset_pause_is_capped() {
+        let limiter = RateLimiter::new(&config(Some(5)));
+        let reset = (chrono::Utc::now().timestamp() + 86_400).to_string();
+        limiter.observe(&exhausted(&reset)).await;
+        assert!(limiter.paused_for().await.unwrap() <= Duration::from_secs(5));
+
+        let limiter = RateLimiter::new(&config(None));
+        limiter.observe(&exhausted("3")).await;
+        let paused = limiter.paused_for().await.unwrap();
+        assert!(paused > Duration::from_secs(2) && paused <= Duration::from_secs(3));
+    }
+
+    #[test]
+    fn limiters_are_shared_per_host() {
+        let limiters = RateLimiters::default();
+        let source = limiters.for_url("https://api.example.com/items", Some(&config(None))).unwrap();
+        let enrich = limiters.for_url("https://API.example.com:443/items/{id}", None).unwrap();
+        assert!(Arc::ptr_eq(&source, &enrich));
+
+        assert!(limiters.for_url("https://other.example.com/", None).is_none());
+        let other = limiters.for_url("http://api.example.com/", Some(&config(None))).unwrap();
+        assert!(!Arc::ptr_eq(&source, &other));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::config::settings::RetryConfig;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::time::Duration;
+use tracing::warn;
+
+const DEFAULT_RETRY_STATUSES: [u16; 4] = [429, 502, 503, 504];
+
+/// 由 `RetryConfig` 計算每次重試的等待時間
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: f64,
+    jitter: bool,
+    retry_statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            retry_statuses: DEFAULT_RETRY_STATUSES.to_vec(),
+        }
+    }
+}
+
+impl From<&RetryConfig> for RetryPolicy {
+    fn from(config: &RetryConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            initial_delay: Duration::from_millis(config.initial_delay_ms),
+            max_delay: Duration::from_millis(config.max_delay_ms),
+            multiplier: config.backoff_multiplier.max(1.0),
+            jitter: config.jitter.unwrap_or(true),
+            retry_statuses: config
+                .retry_on_status
+                .clone()
+                .unwrap_or_else(|| DEFAULT_RETRY_STATUSES.to_vec()),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: Option<&RetryConfig>) -> Self {
+        config.map(Self::from).unwrap_or_default()
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn is_retryable_status(&self, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status.as_u16())
+    }
+
+    /// 第 `attempt` 次（從 1 開始）失敗後的等待時間：initial * multiplier^(attempt-1)，上限 max_delay。
+    /// 啟用 jitter 時取 [delay/2, delay] 之間的隨機值，避免多個 worker 同時重試
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        let delay = Duration::from_secs_f64(delay.min(self.max_delay.as_secs_f64()));
+
+        if self.jitter && !delay.is_zero() {
+            let half = delay / 2;
+            half + half.mul_f64(rand::random_range(0.0..=1.0))
+        } else {
+            delay
+        }
+    }
+
+    /// 解析 `Retry-After` 標頭（秒數或 HTTP-date），超過 `max_delay` 時以 `max_delay` 為上限，
+    /// 避免異常的標頭讓流程停頓數小時
+    pub fn retry_after(&self, headers: &HeaderMap) -> Option<Duration> {
+        let requested = Self::parse_retry_after(headers)?;
+        if requested > self.max_delay {
+            warn!(
+                "Retry-After of {:?} exceeds max_delay, waiting {:?} instead",
+                requested, self.max_delay
+            );
+            return Some(self.max_delay);
+        }
+        Some(requested)
+    }
+
+    fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+        let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+        if let Ok(seconds) = value.parse::<u64>() {
+            return Some(Duration::from_secs(seconds));
+        }
+
+        let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+        let wait = date.with_timezone(&chrono::Utc) - chrono::Utc::now();
+        Some(wait.to_std().unwrap_or(Duration::ZERO))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::from(&RetryConfig {
+            max_attempts: 3,
+            initial_delay_ms: 100,
+            max_delay_ms: 5_000,
+            backoff_multiplier: 2.0,
+            jitter: Some(false),
+            retry_on_status: None,
+        })
+    }
+
+    fn retry_after(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn retry_after_is_capped_at_max_delay() {
+        let policy = policy();
+        assert_eq!(policy.retry_after(&retry_after("2")), Some(Duration::from_secs(2)));
+        assert_eq!(policy.retry_after(&retry_after("86400")), Some(Duration::from_secs(5)));
+
+        let far_future = (chrono::Utc::now() + chrono::Duration::hours(3)).to_rfc2822();
+        assert_eq!(policy.retry_after(&retry_after(&far_future)), Some(Duration::from_secs(5)));
+        assert_eq!(policy.retry_after(&retry_after("Mon, 01 Jan 2001 00:00:00 GMT")), Some(Duration::ZERO));
+        assert_eq!(policy.retry_after(&retry_after("soon")), None);
+        assert_eq!(policy.retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn backoff_grows_up_to_max_delay() {
+        let policy = policy();
+        assert_eq!(policy.delay_for(1), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(400));
+        assert_eq!(policy.delay_for(10), Duration::from_secs(5));
+    }
+}
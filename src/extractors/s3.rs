@@ -0,0 +1,366 @@
+use crate::config::settings::{
+    AuthConfig, AuthCredentials, AuthType, AwsCredentials, FileFormat, RetryConfig, SigV4Config,
+};
+use crate::extractors::api_client::{ApiClient, RequestBody};
+use crate::extractors::payload::{xml_to_json, Download};
+use crate::extractors::signing::uri_encode;
+use crate::utils::error::{EtlError, Result};
+use regex::Regex;
+use reqwest::{Response, Url};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// S3 以 500 InternalError 與 503 SlowDown 要求稍後重試
+const S3_RETRY_STATUSES: [u16; 5] = [429, 500, 502, 503, 504];
+
+/// 透過 `ApiClient` 以 SigV4 簽署 S3 REST API 請求，支援 S3 相容服務
+pub struct S3Client {
+    client: ApiClient,
+    auth: AuthConfig,
+    /// 物件 key 之前的網址，例如 `https://bucket.s3.ap-northeast-1.amazonaws.com` 或 `http://localhost:9000/bucket`
+    bucket_url: Url,
+    retry: RetryConfig,
+}
+
+impl S3Client {
+    /// 未設定 `endpoint` 時使用 AWS 的 virtual-hosted 網址；設定時預設 path-style
+    pub fn new(
+        bucket: &str,
+        region: &str,
+        credentials: Option<AwsCredentials>,
+        endpoint: Option<&str>,
+        path_style: Option<bool>,
+    ) -> Result<Self> {
+        let default_endpoint = format!("https://s3.{}.amazonaws.com", region);
+        let endpoint = endpoint.unwrap_or(&default_endpoint);
+        let mut bucket_url = Url::parse(endpoint)
+            .map_err(|e| EtlError::ConfigError(format!("Invalid S3 endpoint {}: {}", endpoint, e)))?;
+        if path_style.unwrap_or(endpoint != default_endpoint) {
+            let path = format!("{}/{}", bucket_url.path().trim_end_matches('/'), uri_encode(bucket.as_bytes()));
+            bucket_url.set_path(&path);
+        } else {
+            let host = format!("{}.{}", bucket, bucket_url.host_str().unwrap_or_default());
+            bucket_url
+                .set_host(Some(&host))
+                .map_err(|e| EtlError::ConfigError(format!("Invalid S3 bucket name {}: {}", bucket, e)))?;
+            bucket_url.set_path("");
+        }
+
+        Ok(Self {
+            client: ApiClient::new(),
+            auth: AuthConfig {
+                auth_type: AuthType::AwsSigV4,
+                credentials: AuthCredentials {
+                    sigv4: Some(SigV4Config {
+                        region: region.to_string(),
+                        service: "s3".to_string(),
+                        credentials,
+                        unsigned_payload: None,
+                        double_encode_path: None,
+                    }),
+                    ..Default::default()
+                },
+            },
+            bucket_url,
+            retry: RetryConfig {
+                max_attempts: 3,
+                initial_delay_ms: 500,
+                max_delay_ms: 30_000,
+                backoff_multiplier: 2.0,
+                jitter: None,
+                retry_on_status: None,
+            },
+        })
+    }
+
+    /// 覆寫重試設定；未指定 `retry_on_status` 時仍會重試 S3 的 500 與 503
+    pub fn with_retry(mut self, retry: Option<RetryConfig>) -> Self {
+        if let Some(retry) = retry {
+            self.retry = retry;
+        }
+        self
+    }
+
+    /// 依 key 樣式列出要讀取的物件：一般 key 原樣回傳，前綴與萬用字元會列出 bucket 內容
+    pub async fn resolve_keys(&self, pattern: &str) -> Result<Vec<String>> {
+        let wildcard = pattern.find(['*', '?']);
+        if wildcard.is_none() && !pattern.ends_with('/') {
+            return Ok(vec![pattern.to_string()]);
+        }
+
+        let prefix = &pattern[..wildcard.unwrap_or(pattern.len())];
+        let matcher = wildcard.map(|_| Self::glob_regex(pattern)).transpose()?;
+        let keys: Vec<String> = self
+            .list_objects(prefix)
+            .await?
+            .into_iter()
+            // 以 `/` 結尾的是主控台建立的資料夾標記，不是資料
+            .filter(|key| !key.ends_with('/'))
+            .filter(|key| matcher.as_ref().is_none_or(|m| m.is_match(key)))
+            .collect();
+        if keys.is_empty() {
+            return Err(EtlError::ConfigError(format!(
+                "No S3 objects match {} in {}",
+                pattern, self.bucket_url
+            )));
+        }
+        debug!("S3 pattern {} matched {} objects", pattern, keys.len());
+        Ok(keys)
+    }
+
+    /// 以 ListObjectsV2 列出前綴下的所有 key，依 continuation token 逐頁抓取
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if let Some(token) = &continuation_token {
+                query.push(("continuation-token", token));
+            }
+            let response = self.send("GET", "", &query, None, None).await?;
+            let page = ListPage::parse(&response.text().await?)?;
+            keys.extend(page.keys);
+            match page.next_continuation_token {
+                Some(token) if page.is_truncated => continuation_token = Some(token),
+                _ => return Ok(keys),
+            }
+        }
+    }
+
+    /// 下載物件到暫存檔
+    pub async fn get_object(&self, key: &str) -> Result<Download> {
+        let response = self.send("GET", key, &[], None, None).await?;
+        Download::from_response(response).await
+    }
+
+    /// 送出簽署後的請求，`key` 為空字串時對 bucket 本身操作；非 2xx 回應的錯誤訊息包含 S3 的 `Code` 與 `Message`
+    pub async fn send(
+        &self,
+        method: &str,
+        key: &str,
+        query: &[(&str, &str)],
+        headers: Option<HashMap<String, String>>,
+        body: Option<RequestBody>,
+    ) -> Result<Response> {
+        let url = self.url(key, query);
+        let mut retry = self.retry.clone();
+        retry.retry_on_status.get_or_insert_with(|| S3_RETRY_STATUSES.to_vec());
+        let response = self
+            .client
+            .fetch_unchecked(
+                url.as_str(),
+                Some(method.to_string()),
+                headers,
+                Some(self.auth.clone()),
+                Some(retry),
+                body,
+            )
+            .await?;
+        if response.status().is_success() {
+            return Ok(response);
+        }
+
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        Err(EtlError::HttpError(status.as_u16(), Self::error_message(method, key, status, &text)))
+    }
+
+    /// HEAD 等沒有 body 的錯誤回應只回報狀態碼
+    fn error_message(method: &str, key: &str, status: reqwest::StatusCode, body: &str) -> String {
+        let document = xml_to_json(body.as_bytes()).unwrap_or_default();
+        let error = &document["Error"];
+        match error["Code"].as_str() {
+            Some(code) => format!(
+                "S3 {} /{} failed: {} {}: {}",
+                method,
+                key,
+                status,
+                code,
+                error["Message"].as_str().unwrap_or_default()
+            ),
+            None => format!("S3 {} /{} failed: {}", method, key, status),
+        }
+    }
+
+    /// 依副檔名對應檔案格式，無法判斷時回傳 `None`
+    pub fn format_for_key(key: &str) -> Option<FileFormat> {
+        let extension = key.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(FileFormat::Json),
+            "csv" => Some(FileFormat::Csv { delimiter: None, has_headers: None }),
+            "tsv" => Some(FileFormat::Tsv),
+            "xlsx" | "xls" => Some(FileFormat::Excel),
+            "parquet" => Some(FileFormat::Parquet),
+            "zip" => Some(FileFormat::Zip { extract_path: None, target_files: Vec::new(), password: None }),
+            _ => None,
+        }
+    }
+
+    /// key 的每個路徑區段與查詢參數依 RFC 3986 編碼，與 SigV4 正規化的結果一致
+    fn url(&self, key: &str, query: &[(&str, &str)]) -> Url {
+        let mut url = self.bucket_url.clone();
+        let encoded_key: Vec<String> = key.split('/').map(|segment| uri_encode(segment.as_bytes())).collect();
+        url.set_path(&format!("{}/{}", self.bucket_url.path().trim_end_matches('/'), encoded_key.join("/")));
+        if !query.is_empty() {
+            let query: Vec<String> = query
+                .iter()
+                .map(|(name, value)| format!("{}={}", uri_encode(name.as_bytes()), uri_encode(value.as_bytes())))
+                .collect();
+            url.set_query(Some(&query.join("&")));
+        }
+        url
+    }
+
+    /// `**` 跨越 `/`，`*` 與 `?` 只比對單一路徑區段
+    fn glob_regex(pattern: &str) -> Result<Regex> {
+        let mut regex = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    regex.push_str(".*");
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                other => regex.push_str(&regex::escape(&other.to_string())),
+            }
+        }
+        regex.push('$');
+        Regex::new(&regex).map_err(|e| EtlError::ConfigError(format!("Invalid S3 key pattern {}: {}", pattern, e)))
+    }
+}
+
+/// ListObjectsV2 回應中需要的欄位
+#[derive(Debug, Default)]
+struct ListPage {
+    keys: Vec<String>,
+    is_truncated: bool,
+    next_continuation_token: Option<String>,
+}
+
+impl ListPage {
+    fn parse(xml: &str) -> Result<Self> {
+        let document = xml_to_json(xml.as_bytes())
+            .map_err(|e| EtlError::ParseError(format!("Invalid S3 list response: {}", e)))?;
+        let result = &document["ListBucketResult"];
+        // 只有一個物件時 `Contents` 不是陣列
+        let contents = match &result["Contents"] {
+            serde_json::Value::Array(items) => items.iter().collect(),
+            serde_json::Value::Null => Vec::new(),
+            item => vec![item],
+        };
+        Ok(Self {
+            keys: contents.iter().filter_map(|c| c["Key"].as_str().map(str::to_string)).collect(),
+            is_truncated: result["IsTruncated"].as_str() == Some("true"),
+            next_continuation_token: result["NextContinuationToken"].as_str().map(str::to_string),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{TestResponse, TestServer};
+
+    fn client(endpoint: Option<&str>) -> S3Client {
+        let credentials = AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "secret".to_string(),
+            session_token: None,
+        };
+        S3Client::new("my-bucket", "eu-west-1", Some(credentials), endpoint, None).unwrap()
+    }
+
+    #[test]
+    fn glob_regex_matches_segments() {
+        let single = S3Client::glob_regex("logs/*.csv").unwrap();
+        assert!(single.is_match("logs/a.csv"));
+        assert!(!single.is_match("logs/2026/a.csv"));
+        assert!(!single.is_match("logs/a.csv.bak"));
+
+        let recursive = S3Client::glob_regex("logs/**.csv").unwrap();
+        assert!(recursive.is_match("logs/2026/01/a.csv"));
+
+        let one_char = S3Client::glob_regex("data/part-?.json").unwrap();
+        assert!(one_char.is_match("data/part-1.json"));
+        assert!(!one_char.is_match("data/part-10.json"));
+        assert!(!one_char.is_match("data/part-/.json"));
+
+        // 其他正規表示式字元照字面比對
+        let literal = S3Client::glob_regex("a+b (1)/*.csv").unwrap();
+        assert!(literal.is_match("a+b (1)/x.csv"));
+        assert!(!literal.is_match("aab (1)/x.csv"));
+    }
+
+    #[test]
+    fn list_page_with_multiple_objects() {
+        let page = ListPage::parse(
+            "<ListBucketResult><IsTruncated>true</IsTruncated>\
+             <Contents><Key>a.csv</Key></Contents><Contents><Key>b.csv</Key></Contents>\
+             <NextContinuationToken>token/1=</NextContinuationToken></ListBucketResult>",
+        )
+        .unwrap();
+        assert_eq!(page.keys, ["a.csv", "b.csv"]);
+        assert!(page.is_truncated);
+        assert_eq!(page.next_continuation_token.as_deref(), Some("token/1="));
+    }
+
+    #[test]
+    fn list_page_with_single_or_no_objects() {
+        let single = ListPage::parse(
+            "<ListBucketResult><IsTruncated>false</IsTruncated>\
+             <Contents><Key>only.csv</Key><Size>10</Size></Contents></ListBucketResult>",
+        )
+        .unwrap();
+        assert_eq!(single.keys, ["only.csv"]);
+        assert!(!single.is_truncated);
+        assert!(single.next_continuation_token.is_none());
+
+        let empty = ListPage::parse("<ListBucketResult><IsTruncated>false</IsTruncated></ListBucketResult>").unwrap();
+        assert!(empty.keys.is_empty());
+    }
+
+    #[test]
+    fn url_encodes_key_segments_and_query() {
+        let virtual_hosted = client(None);
+        assert_eq!(
+            virtual_hosted.url("reports/2026 Q1/a+b.csv", &[]).as_str(),
+            "https://my-bucket.s3.eu-west-1.amazonaws.com/reports/2026%20Q1/a%2Bb.csv"
+        );
+
+        let path_style = client(Some("http://localhost:9000"));
+        assert_eq!(
+            path_style.url("", &[("list-type", "2"), ("prefix", "logs/2026 Q1/"), ("continuation-token", "a/b=")]).as_str(),
+            "http://localhost:9000/my-bucket/?list-type=2&prefix=logs%2F2026%20Q1%2F&continuation-token=a%2Fb%3D"
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_keys_follows_continuation_tokens() {
+        let server = TestServer::start(|request| {
+            let body = if request.query().contains("continuation-token=next%2F1") {
+                "<ListBucketResult><IsTruncated>false</IsTruncated>\
+                 <Contents><Key>logs/2026/c.csv</Key></Contents></ListBucketResult>"
+            } else {
+                "<ListBucketResult><IsTruncated>true</IsTruncated>\
+                 <Contents><Key>logs/</Key></Contents><Contents><Key>logs/a.csv</Key></Contents>\
+                 <Contents><Key>logs/b.json</Key></Contents>\
+                 <NextContinuationToken>next/1</NextContinuationToken></ListBucketResult>"
+            };
+            TestResponse::new(200, body)
+        })
+        .await;
+        let client = client(Some(&server.url));
+
+        assert_eq!(client.resolve_keys("logs/**.csv").await.unwrap(), ["logs/a.csv", "logs/2026/c.csv"]);
+        assert_eq!(client.resolve_keys("logs/*.csv").await.unwrap(), ["logs/a.csv"]);
+        assert_eq!(client.resolve_keys("logs/a.csv").await.unwrap(), ["logs/a.csv"]);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0].path(), "/my-bucket/");
+        assert_eq!(requests[0].query(), "list-type=2&prefix=logs%2F");
+        assert!(requests[0].header("authorization").unwrap().starts_with("AWS4-HMAC-SHA256 "));
+    }
+}
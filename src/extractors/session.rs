@@ -0,0 +1,433 @@
+use crate::config::settings::{ApiRequestBody, SessionLoginConfig, TokenSource};
+use crate::utils::error::{EtlError, Result};
+use crate::utils::helpers::{resolve_secret, select_path};
+use chrono::{NaiveDateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, COOKIE, SET_COOKIE};
+use reqwest::{Client, Method, Response, Url};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{debug, info};
+
+const DEFAULT_RELOGIN_STATUSES: [u16; 2] = [401, 403];
+
+#[derive(Debug, Clone)]
+struct Cookie {
+    name: String,
+    value: String,
+    domain: String,
+    /// 沒有 `Domain` 屬性時只送回設定 cookie 的主機
+    host_only: bool,
+    path: String,
+    secure: bool,
+    expires_at: Option<Instant>,
+}
+
+impl Cookie {
+    /// 解析 `Set-Cookie`，`Domain` 與請求主機不符時忽略
+    fn parse(header: &str, url: &Url) -> Option<Self> {
+        let host = url.host_str()?.to_lowercase();
+        let mut parts = header.split(';').map(str::trim);
+        let (name, value) = parts.next()?.split_once('=')?;
+        let mut cookie = Cookie {
+            name: name.trim().to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: Self::default_path(url),
+            secure: false,
+            expires_at: None,
+        };
+        if cookie.name.is_empty() {
+            return None;
+        }
+
+        let mut max_age = None;
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            match key.trim().to_lowercase().as_str() {
+                "domain" => {
+                    let domain = value.trim().trim_start_matches('.').to_lowercase();
+                    if !domain.is_empty() {
+                        if !Self::domain_matches(&host, &domain) {
+                            return None;
+                        }
+                        cookie.domain = domain;
+                        cookie.host_only = false;
+                    }
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.trim().parse::<i64>().ok(),
+                "expires" if cookie.expires_at.is_none() => {
+                    cookie.expires_at = Self::parse_expires(value.trim()).map(Self::instant_from_now);
+                }
+                _ => {}
+            }
+        }
+        // Max-Age 優先於 Expires
+        if let Some(seconds) = max_age {
+            cookie.expires_at = Some(Self::instant_from_now(seconds));
+        }
+        Some(cookie)
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str().map(str::to_lowercase) else {
+            return false;
+        };
+        let domain_ok = if self.host_only {
+            host == self.domain
+        } else {
+            Self::domain_matches(&host, &self.domain)
+        };
+        let path = url.path();
+        let path_ok = path == self.path
+            || (path.starts_with(&self.path)
+                && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')));
+        domain_ok && path_ok && (!self.secure || url.scheme() == "https") && !self.is_expired()
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= Instant::now())
+    }
+
+    fn domain_matches(host: &str, domain: &str) -> bool {
+        host == domain || host.ends_with(&format!(".{}", domain))
+    }
+
+    /// 請求路徑的目錄部分，例如 `/auth/login` -> `/auth`
+    fn default_path(url: &Url) -> String {
+        match url.path().rfind('/') {
+            Some(0) | None => "/".to_string(),
+            Some(index) => url.path()[..index].to_string(),
+        }
+    }
+
+    fn parse_expires(value: &str) -> Option<i64> {
+        let expires = chrono::DateTime::parse_from_rfc2822(value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+            .or_else(|| {
+                NaiveDateTime::parse_from_str(value, "%a, %d-%b-%Y %H:%M:%S GMT")
+                    .ok()
+                    .map(|naive| naive.and_utc())
+            })?;
+        Some((expires - Utc::now()).num_seconds())
+    }
+
+    fn instant_from_now(seconds: i64) -> Instant {
+        let now = Instant::now();
+        match u64::try_from(seconds) {
+            Ok(seconds) if seconds > 0 => now + Duration::from_secs(seconds),
+            _ => now,
+        }
+    }
+}
+
+/// 依 domain、path 與 Secure 屬性決定要送出的 cookie
+#[derive(Debug, Default, Clone)]
+pub struct CookieJar {
+    cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+    pub fn store(&mut self, url: &Url, headers: &HeaderMap) {
+        for header in headers.get_all(SET_COOKIE).iter().filter_map(|v| v.to_str().ok()) {
+            let Some(cookie) = Cookie::parse(header, url) else {
+                continue;
+            };
+            self.cookies.retain(|existing| {
+                !(existing.name == cookie.name && existing.domain == cookie.domain && existing.path == cookie.path)
+            });
+            // 已過期的 cookie 代表伺服器要求刪除
+            if !cookie.is_expired() {
+                self.cookies.push(cookie);
+            }
+        }
+    }
+
+    /// `Cookie` 標頭的值，較長（較精確）的 path 在前
+    pub fn header_value(&self, url: &Url) -> Option<String> {
+        let mut matching: Vec<&Cookie> = self.cookies.iter().filter(|c| c.matches(url)).collect();
+        if matching.is_empty() {
+            return None;
+        }
+        matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        Some(
+            matching
+                .iter()
+                .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+                .collect::<Vec<_>>()
+                .join("; "),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct Session {
+    jar: CookieJar,
+    token: Option<String>,
+    expires_at: Option<Instant>,
+}
+
+impl Session {
+    fn is_fresh(&self) -> bool {
+        self.expires_at.is_none_or(|expires_at| Instant::now() < expires_at)
+    }
+}
+
+/// 以登入流程取得並快取 session，失效時重新登入
+#[derive(Default)]
+pub struct SessionManager {
+    sessions: Mutex<HashMap<String, Session>>,
+}
+
+impl SessionManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 回傳要加到請求上的 `Cookie` 與 token 標頭，沒有有效 session 時先登入
+    pub async fn headers(&self, client: &Client, config: &SessionLoginConfig, url: &Url) -> Result<HeaderMap> {
+        let key = Self::cache_key(config);
+        // 持有鎖直到登入完成，避免並行請求重複登入
+        let mut sessions = self.sessions.lock().await;
+        if !sessions.get(&key).is_some_and(Session::is_fresh) {
+            let session = Self::login(client, config).await?;
+            sessions.insert(key.clone(), session);
+        }
+        let session = sessions.get(&key).expect("session was just inserted");
+
+        let mut headers = HeaderMap::new();
+        if let Some(cookies) = session.jar.header_value(url) {
+            let value = HeaderValue::from_str(&cookies)
+                .map_err(|e| EtlError::AuthError(format!("Invalid session cookie: {}", e)))?;
+            headers.insert(COOKIE, value);
+        }
+        if let (Some(token), Some(token_config)) = (&session.token, &config.token) {
+            let name = token_config.header_name.as_deref().unwrap_or("Authorization");
+            let value = token_config.format.as_deref().unwrap_or("Bearer {token}").replace("{token}", token);
+            headers.insert(
+                HeaderName::from_str(name)
+                    .map_err(|e| EtlError::ConfigError(format!("Invalid header name '{}': {}", name, e)))?,
+                HeaderValue::from_str(&value)
+                    .map_err(|e| EtlError::AuthError(format!("Invalid session token: {}", e)))?,
+            );
+        }
+        Ok(headers)
+    }
+
+    /// 保存回應中更新的 cookie（部分系統每次回應都會輪替 session id）
+    pub async fn observe(&self, config: &SessionLoginConfig, response: &Response) {
+        if let Some(session) = self.sessions.lock().await.get_mut(&Self::cache_key(config)) {
+            session.jar.store(response.url(), response.headers());
+        }
+    }
+
+    /// 狀態碼符合 `relogin_statuses`，或被轉址到登入頁
+    pub fn is_expired(config: &SessionLoginConfig, response: &Response) -> bool {
+        let status = response.status().as_u16();
+        let status_expired = match &config.relogin_statuses {
+            Some(statuses) => statuses.contains(&status),
+            None => DEFAULT_RELOGIN_STATUSES.contains(&status),
+        };
+        let redirected_to_login = Url::parse(&config.login_url).is_ok_and(|login| {
+            let url = response.url();
+            url.origin() == login.origin() && url.path() == login.path()
+        });
+        status_expired || redirected_to_login
+    }
+
+    pub async fn invalidate(&self, config: &SessionLoginConfig) {
+        self.sessions.lock().await.remove(&Self::cache_key(config));
+    }
+
+    async fn login(client: &Client, config: &SessionLoginConfig) -> Result<Session> {
+        let username = config.username.clone().unwrap_or_default();
+        let password = config.password.as_ref().map(resolve_secret).transpose()?.unwrap_or_default();
+        let fill = |value: &str| value.replace("{username}", &username).replace("{password}", &password);
+
+        let method = Method::from_str(config.method.as_deref().unwrap_or("POST"))
+            .map_err(|e| EtlError::ConfigError(format!("Invalid HTTP method: {}", e)))?;
+        let mut request = client.request(method, &config.login_url);
+        for (name, value) in config.headers.iter().flatten() {
+            request = request.header(name, fill(value));
+        }
+        request = match &config.body {
+            Some(ApiRequestBody::Json(body)) => request.json(&Self::fill_json(body, &fill)),
+            Some(ApiRequestBody::Form(fields)) => {
+                let mut fields: Vec<(String, String)> =
+                    fields.iter().map(|(k, v)| (k.clone(), fill(v))).collect();
+                fields.sort();
+                request.form(&fields)
+            }
+            None => request,
+        };
+
+        debug!("Logging in to {}", config.login_url);
+        let response = request.send().await?;
+        let status = response.status();
+        // 登入請求不跟隨轉址，3xx 也視為成功
+        if !(status.is_success() || status.is_redirection()) {
+            let body = response.text().await.unwrap_or_default();
+            return Err(EtlError::AuthError(format!(
+                "Login to {} failed with {}: {}",
+                config.login_url,
+                status,
+                body.chars().take(200).collect::<String>()
+            )));
+        }
+
+        let mut jar = CookieJar::default();
+        jar.store(response.url(), response.headers());
+
+        let token = match config.token.as_ref().map(|t| &t.source) {
+            Some(TokenSource::Header { name }) => Some(
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string)
+                    .ok_or_else(|| {
+                        EtlError::AuthError(format!("Login response has no {} header", name))
+                    })?,
+            ),
+            Some(TokenSource::JsonPath { path }) => {
+                let body: serde_json::Value = response.json().await.map_err(|e| {
+                    EtlError::AuthError(format!("Invalid login response from {}: {}", config.login_url, e))
+                })?;
+                let token = match select_path(&body, path) {
+                    Some(serde_json::Value::String(token)) => token.clone(),
+                    Some(value) if !value.is_null() => value.to_string(),
+                    _ => {
+                        return Err(EtlError::AuthError(format!(
+                            "Login response has no token at '{}'",
+                            path
+                        )))
+                    }
+                };
+                Some(token)
+            }
+            None => None,
+        };
+
+        if jar.is_empty() && token.is_none() {
+            return Err(EtlError::AuthError(format!(
+                "Login to {} returned neither a session cookie nor a token",
+                config.login_url
+            )));
+        }
+
+        info!("Logged in to {}", config.login_url);
+        Ok(Session {
+            jar,
+            token,
+            expires_at: config.ttl_seconds.map(|secs| Instant::now() + Duration::from_secs(secs)),
+        })
+    }
+
+    fn fill_json(value: &serde_json::Value, fill: &impl Fn(&str) -> String) -> serde_json::Value {
+        match value {
+            serde_json::Value::String(s) => serde_json::Value::String(fill(s)),
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.iter().map(|item| Self::fill_json(item, fill)).collect())
+            }
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.iter().map(|(k, v)| (k.clone(), Self::fill_json(v, fill))).collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn cache_key(config: &SessionLoginConfig) -> String {
+        format!("{}|{}", config.login_url, config.username.as_deref().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(value: &str) -> Url {
+        Url::parse(value).unwrap()
+    }
+
+    fn jar(request_url: &str, set_cookies: &[&str]) -> CookieJar {
+        let mut headers = HeaderMap::new();
+        for value in set_cookies {
+            headers.append(SET_COOKIE, HeaderValue::from_str(value).unwrap());
+        }
+        let mut jar = CookieJar::default();
+        jar.store(&url(request_url), &headers);
+        jar
+    }
+
+    #[test]
+    fn parse_attributes() {
+        let cookie = Cookie::parse(
+            "sid=\"abc\"; Domain=.Example.com; Path=/api; Secure; HttpOnly; Max-Age=3600",
+            &url("https://login.example.com/auth/login"),
+        )
+        .unwrap();
+        assert_eq!((cookie.name.as_str(), cookie.value.as_str()), ("sid", "abc"));
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only);
+        assert_eq!(cookie.path, "/api");
+        assert!(cookie.secure);
+        assert!(!cookie.is_expired());
+
+        let defaults = Cookie::parse("sid=abc", &url("https://Login.example.com/auth/login")).unwrap();
+        assert_eq!(defaults.domain, "login.example.com");
+        assert!(defaults.host_only);
+        assert_eq!(defaults.path, "/auth");
+        assert!(defaults.expires_at.is_none());
+    }
+
+    #[test]
+    fn parse_rejects_foreign_domain_and_empty_name() {
+        let login = url("https://login.example.com/");
+        assert!(Cookie::parse("sid=abc; Domain=other.com", &login).is_none());
+        assert!(Cookie::parse("sid=abc; Domain=ample.com", &login).is_none());
+        assert!(Cookie::parse("=abc", &login).is_none());
+        assert!(Cookie::parse("no-value", &login).is_none());
+    }
+
+    #[test]
+    fn max_age_takes_precedence_over_expires() {
+        let login = url("https://example.com/");
+        let expired = Cookie::parse("sid=abc; Max-Age=0; Expires=Wed, 01 Jan 2120 00:00:00 GMT", &login).unwrap();
+        assert!(expired.is_expired());
+        let past = Cookie::parse("sid=abc; Expires=Thu, 01-Jan-1970 00:00:00 GMT", &login).unwrap();
+        assert!(past.is_expired());
+    }
+
+    #[test]
+    fn header_value_matches_domain_path_and_secure() {
+        let jar = jar(
+            "https://login.example.com/auth/login",
+            &["host=1", "shared=2; Domain=example.com; Path=/", "api=3; Path=/api; Domain=example.com", "tls=4; Secure; Path=/"],
+        );
+
+        assert_eq!(jar.header_value(&url("https://login.example.com/auth/me")).as_deref(), Some("host=1; shared=2; tls=4"));
+        assert_eq!(jar.header_value(&url("https://data.example.com/api/v1")).as_deref(), Some("api=3; shared=2"));
+        // `/apix` 不屬於 `/api`，http 不送 Secure cookie
+        assert_eq!(jar.header_value(&url("http://data.example.com/apix")).as_deref(), Some("shared=2"));
+        assert_eq!(jar.header_value(&url("https://example.org/")), None);
+    }
+
+    #[test]
+    fn expired_cookie_removes_stored_value() {
+        let mut jar = jar("https://example.com/", &["sid=abc; Path=/", "theme=dark; Path=/"]);
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, HeaderValue::from_static("sid=; Path=/; Max-Age=0"));
+        jar.store(&url("https://example.com/logout"), &headers);
+
+        assert_eq!(jar.header_value(&url("https://example.com/")).as_deref(), Some("theme=dark"));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::models::data_types::DataRecord;
+use crate::utils::error::{EtlError, Result};
+use base64::Engine;
+use futures::Stream;
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{Connection, OpenFlags, ToSql};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+/// 讀取端與寫入端之間最多暫存的列數
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// 查詢參數，依位置（`?`、`?1`）或名稱（`:name`）綁定
+#[derive(Debug, Clone, Default)]
+pub enum QueryParams {
+    #[default]
+    None,
+    Positional(Vec<Value>),
+    Named(Vec<(String, Value)>),
+}
+
+impl QueryParams {
+    /// 陣列為位置參數，物件為具名參數（名稱可省略 `:` 前綴）
+    pub fn from_json(params: Option<&serde_json::Value>) -> Result<Self> {
+        match params {
+            None | Some(serde_json::Value::Null) => Ok(Self::None),
+            Some(serde_json::Value::Array(values)) => {
+                Ok(Self::Positional(values.iter().map(to_sql_value).collect()))
+            }
+            Some(serde_json::Value::Object(map)) => Ok(Self::Named(
+                map.iter()
+                    .map(|(name, value)| {
+                        let name = match name.chars().next() {
+                            Some(':' | '@' | '$') => name.clone(),
+                            _ => format!(":{}", name),
+                        };
+                        (name, to_sql_value(value))
+                    })
+                    .collect(),
+            )),
+            Some(other) => Err(EtlError::ConfigError(format!(
+                "Query params must be an array or object, got {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// 以 SQLite 執行查詢，逐列轉為 `DataRecord`
+pub struct SqliteReader {
+    path: String,
+}
+
+impl SqliteReader {
+    pub fn new(connection_string: &str) -> Self {
+        Self { path: database_path(connection_string).to_string() }
+    }
+
+    /// 在背景執行緒逐列讀取，透過有界 channel 傳回，不會一次載入整個結果集
+    pub fn query_stream(&self, query: &str, params: QueryParams) -> impl Stream<Item = Result<DataRecord>> {
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        let path = self.path.clone();
+        let query = query.to_string();
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = Self::send_rows(&path, &query, &params, &sender) {
+                // 接收端已關閉時不需要回報
+                let _ = sender.blocking_send(Err(e));
+            }
+        });
+        futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|item| (item, receiver))
+        })
+    }
+
+    pub async fn query(&self, query: &str, params: QueryParams) -> Result<Vec<DataRecord>> {
+        use futures::TryStreamExt;
+        self.query_stream(query, params).try_collect().await
+    }
+
+    fn send_rows(
+        path: &str,
+        query: &str,
+        params: &QueryParams,
+        sender: &mpsc::Sender<Result<DataRecord>>,
+    ) -> Result<()> {
+        let connection = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(|e| EtlError::DatabaseError(format!("Failed to open SQLite database {}: {}", path, e)))?;
+        let mut statement = connection.prepare(query)?;
+        let columns: Vec<String> = statement.column_names().into_iter().map(str::to_string).collect();
+
+        let mut rows = match params {
+            QueryParams::None => statement.query([])?,
+            QueryParams::Positional(values) => statement.query(rusqlite::params_from_iter(values))?,
+            QueryParams::Named(values) => {
+                let named: Vec<(&str, &dyn ToSql)> =
+                    values.iter().map(|(name, value)| (name.as_str(), value as &dyn ToSql)).collect();
+                statement.query(named.as_slice())?
+            }
+        };
+
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            let mut fields = HashMap::with_capacity(columns.len());
+            for (index, column) in columns.iter().enumerate() {
+                fields.insert(column.clone(), from_sql_value(row.get_ref(index)?));
+            }
+            count += 1;
+            if sender.blocking_send(Ok(DataRecord { fields })).is_err() {
+                debug!("SQLite query on {} cancelled after {} rows", path, count);
+                return Ok(());
+            }
+        }
+        debug!("SQLite query on {} returned {} rows", path, count);
+        Ok(())
+    }
+}
+
+/// 去除 `sqlite://` 或 `sqlite:` 前綴
+pub fn database_path(connection_string: &str) -> &str {
+    connection_string
+        .strip_prefix("sqlite://")
+        .or_else(|| connection_string.strip_prefix("sqlite:"))
+        .unwrap_or(connection_string)
+}
+
+/// 布林存為 0/1，陣列與物件存為 JSON 字串
+pub fn to_sql_value(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(i64::from(*b)),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => n.as_f64().map(Value::Real).unwrap_or(Value::Null),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+/// BLOB 以 base64 字串表示
+fn from_sql_value(value: ValueRef) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => serde_json::Value::Number(i.into()),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        ValueRef::Text(text) => serde_json::Value::String(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(blob) => {
+            serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(blob))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn database() -> (tempfile::TempDir, SqliteReader) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("source.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE users (id INTEGER, name TEXT, score REAL, avatar BLOB);
+                 INSERT INTO users VALUES (1, 'amy', 9.5, x'0102'), (2, 'bob', NULL, NULL), (3, 'cat', 7.0, NULL);",
+            )
+            .unwrap();
+        let reader = SqliteReader::new(&format!("sqlite://{}", path.display()));
+        (dir, reader)
+    }
+
+    async fn names(reader: &SqliteReader, query: &str, params: serde_json::Value) -> Vec<serde_json::Value> {
+        reader
+            .query(query, QueryParams::from_json(Some(&params)).unwrap())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| record.fields["name"].clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn positional_params() {
+        let (_dir, reader) = database();
+        let query = "SELECT name FROM users WHERE id >= ? AND name != ?2 ORDER BY id";
+        assert_eq!(names(&reader, query, json!([2, "cat"])).await, [json!("bob")]);
+    }
+
+    #[tokio::test]
+    async fn named_params_with_or_without_prefix() {
+        let (_dir, reader) = database();
+        let query = "SELECT name FROM users WHERE id > :min AND score IS NOT NULL ORDER BY id";
+        assert_eq!(names(&reader, query, json!({"min": 0})).await, [json!("amy"), json!("cat")]);
+        assert_eq!(names(&reader, query, json!({":min": 1})).await, [json!("cat")]);
+    }
+
+    #[tokio::test]
+    async fn column_values_are_converted() {
+        let (_dir, reader) = database();
+        let records = reader.query("SELECT * FROM users WHERE id = 1", QueryParams::None).await.unwrap();
+        assert_eq!(
+            serde_json::to_value(&records[0].fields).unwrap(),
+            json!({"id": 1, "name": "amy", "score": 9.5, "avatar": "AQI="})
+        );
+    }
+
+    #[test]
+    fn invalid_params_are_rejected() {
+        assert!(QueryParams::from_json(Some(&json!("id"))).is_err());
+        assert!(matches!(QueryParams::from_json(None).unwrap(), QueryParams::None));
+    }
+}
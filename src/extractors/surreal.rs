@@ -0,0 +1,111 @@
+use crate::utils::error::{EtlError, Result};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use surrealdb::engine::any::Any;
+use surrealdb::method::Query;
+use surrealdb::Surreal;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+pub const DEFAULT_NAMESPACE: &str = "etl";
+pub const DEFAULT_DATABASE: &str = "etl";
+
+/// 同一個連線字串在程序內共用連線：記憶體資料庫才能在來源與輸出之間共用，檔案資料庫也不會被重複開啟
+static CONNECTIONS: LazyLock<Mutex<HashMap<String, Surreal<Any>>>> = LazyLock::new(Default::default);
+
+/// SurrealQL 中直接寫入的名稱（namespace、database、資料表）只接受一般識別字
+pub(crate) static IDENTIFIER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").expect("valid identifier regex"));
+
+/// 共用連線上的一組 namespace 與 database。不呼叫會影響所有複本的 `use_ns` / `use_db`，
+/// 而是在每個查詢前加上 `USE`，同一連線上的來源與輸出可各自使用不同的 namespace
+#[derive(Clone)]
+pub struct SurrealSession {
+    db: Surreal<Any>,
+    scope: String,
+}
+
+impl SurrealSession {
+    pub fn query(&self, query: impl AsRef<str>) -> Query<'_, Any> {
+        self.db.query(format!("{}\n{}", self.scope, query.as_ref()))
+    }
+}
+
+/// 連線到內嵌的 SurrealDB（`mem://`、`surrealkv://<path>`）
+pub async fn connect(
+    connection_string: &str,
+    namespace: Option<&str>,
+    database: Option<&str>,
+) -> Result<SurrealSession> {
+    let namespace = namespace.unwrap_or(DEFAULT_NAMESPACE);
+    let database = database.unwrap_or(DEFAULT_DATABASE);
+    for name in [namespace, database] {
+        if !IDENTIFIER.is_match(name) {
+            return Err(EtlError::ConfigError(format!(
+                "Invalid SurrealDB namespace or database '{}': use letters, digits and underscores",
+                name
+            )));
+        }
+    }
+
+    let db = {
+        let mut connections = CONNECTIONS.lock().await;
+        match connections.get(connection_string) {
+            Some(db) => db.clone(),
+            None => {
+                let db = surrealdb::engine::any::connect(connection_string).await.map_err(|e| {
+                    EtlError::DatabaseError(format!("Failed to open SurrealDB {}: {}", connection_string, e))
+                })?;
+                connections.insert(connection_string.to_string(), db.clone());
+                db
+            }
+        }
+    };
+    Ok(SurrealSession { db, scope: format!("USE NS {} DB {};", namespace, database) })
+}
+
+/// 執行 SurrealQL 查詢，回傳最後一個語句的結果
+pub struct SurrealReader {
+    db: SurrealSession,
+}
+
+impl SurrealReader {
+    pub async fn connect(connection_string: &str, namespace: Option<&str>, database: Option<&str>) -> Result<Self> {
+        Ok(Self { db: connect(connection_string, namespace, database).await? })
+    }
+
+    /// `params` 的每個鍵以 `$name` 綁定
+    pub async fn query(&self, query: &str, params: Option<&serde_json::Value>) -> Result<Vec<serde_json::Value>> {
+        let mut request = self.db.query(query);
+        match params {
+            None | Some(serde_json::Value::Null) => {}
+            Some(serde_json::Value::Object(map)) => {
+                for (name, value) in map {
+                    request = request.bind((name.trim_start_matches('$').to_string(), value.clone()));
+                }
+            }
+            Some(other) => {
+                return Err(EtlError::ConfigError(format!(
+                    "SurrealDB query params must be an object, got {}",
+                    other
+                )))
+            }
+        }
+
+        let mut response = request.await?.check()?;
+        // 第一個語句是 `USE`
+        let Some(last) = response.num_statements().checked_sub(1).filter(|last| *last > 0) else {
+            return Ok(Vec::new());
+        };
+        let result: surrealdb::Value = response.take(last)?;
+        // record ID 轉為 `table:id` 字串，日期轉為 RFC 3339 字串
+        let rows = match result.into_inner().into_json() {
+            serde_json::Value::Array(rows) => rows,
+            serde_json::Value::Null => Vec::new(),
+            other => vec![other],
+        };
+        debug!("SurrealDB query returned {} rows", rows.len());
+        Ok(rows)
+    }
+}
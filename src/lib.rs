@@ -6,3 +6,5 @@ pub mod loaders;
 pub mod utils;
 pub mod models;
 pub mod pipeline;
+#[cfg(test)]
+mod test_support;
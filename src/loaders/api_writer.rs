@@ -0,0 +1,322 @@
+use crate::config::settings::{AuthConfig, BatchFormat, OutputDestination, OutputOptions, RetryConfig};
+use crate::extractors::api_client::{ApiClient, RequestBody};
+use crate::models::data_types::DataRecord;
+use crate::utils::error::{EtlError, Result};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+const DEFAULT_DEAD_LETTER_PATH: &str = "dead_letter.ndjson";
+const RECORDS_PLACEHOLDER: &str = "{records}";
+
+/// 將記錄分批送到 HTTP API，失敗的批次寫入 dead-letter 檔案而不中止整個流程
+pub struct ApiWriter {
+    client: ApiClient,
+    url: String,
+    method: String,
+    headers: Option<HashMap<String, String>>,
+    auth: Option<AuthConfig>,
+    retry: Option<RetryConfig>,
+    batch_size: usize,
+    batch_format: BatchFormat,
+    body_template: Option<serde_json::Value>,
+    dead_letter_path: PathBuf,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ApiWriteSummary {
+    pub batches: usize,
+    pub sent_records: usize,
+    pub failed_batches: usize,
+    pub failed_records: usize,
+}
+
+impl ApiWriter {
+    /// 由 `OutputDestination::Api` 與 `OutputOptions::batch_size` 建立
+    pub fn from_config(destination: &OutputDestination, options: Option<&OutputOptions>) -> Result<Self> {
+        let OutputDestination::Api {
+            url,
+            method,
+            headers,
+            auth,
+            retry,
+            batch_format,
+            body_template,
+            dead_letter_path,
+        } = destination
+        else {
+            return Err(EtlError::ConfigError(
+                "ApiWriter requires an api output destination".to_string(),
+            ));
+        };
+
+        let batch_format = batch_format.clone().unwrap_or_default();
+        if body_template.is_some() && batch_format == BatchFormat::Ndjson {
+            return Err(EtlError::ConfigError(
+                "body_template is only supported with the json_array batch format".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            client: ApiClient::new(),
+            url: url.clone(),
+            method: method.as_deref().unwrap_or("POST").to_uppercase(),
+            headers: headers.clone(),
+            auth: auth.clone(),
+            retry: retry.clone(),
+            batch_size: options
+                .and_then(|o| o.batch_size)
+                .unwrap_or(DEFAULT_BATCH_SIZE)
+                .max(1),
+            batch_format,
+            body_template: body_template.clone(),
+            dead_letter_path: PathBuf::from(
+                dead_letter_path.as_deref().unwrap_or(DEFAULT_DEAD_LETTER_PATH),
+            ),
+        })
+    }
+
+    pub async fn write_records(&self, records: &[DataRecord]) -> Result<ApiWriteSummary> {
+        let mut summary = ApiWriteSummary::default();
+        for (index, batch) in records.chunks(self.batch_size).enumerate() {
+            summary.batches += 1;
+            match self.send_batch(batch).await {
+                Ok(()) => summary.sent_records += batch.len(),
+                Err(e) => {
+                    warn!(
+                        "Batch {} ({} records) to {} failed: {}",
+                        index, batch.len(), self.url, e
+                    );
+                    self.write_dead_letter(index, batch, &e)?;
+                    summary.failed_batches += 1;
+                    summary.failed_records += batch.len();
+                }
+            }
+        }
+
+        if summary.failed_batches > 0 {
+            warn!(
+                "{} of {} batches failed, failed records written to {}",
+                summary.failed_batches,
+                summary.batches,
+                self.dead_letter_path.display()
+            );
+        }
+        info!("Sent {} records to {} in {} batches", summary.sent_records, self.url, summary.batches);
+        Ok(summary)
+    }
+
+    pub fn dead_letter_path(&self) -> &Path {
+        &self.dead_letter_path
+    }
+
+    async fn send_batch(&self, batch: &[DataRecord]) -> Result<()> {
+        let body = self.encode_batch(batch)?;
+        self.client
+            .fetch_with_body(
+                &self.url,
+                Some(self.method.clone()),
+                self.headers.clone(),
+                self.auth.clone(),
+                self.retry.clone(),
+                Some(body),
+            )
+            .await?;
+        Ok(())
+    }
+
+    fn encode_batch(&self, batch: &[DataRecord]) -> Result<RequestBody> {
+        match self.batch_format {
+            BatchFormat::JsonArray => {
+                let records: Vec<serde_json::Value> = batch.iter().map(Self::record_value).collect();
+                let records = serde_json::Value::Array(records);
+                let body = match &self.body_template {
+                    Some(template) => Self::render_template(template, &records),
+                    None => records,
+                };
+                Ok(RequestBody::Json(body))
+            }
+            BatchFormat::Ndjson => {
+                let mut data = Vec::new();
+                for record in batch {
+                    serde_json::to_writer(&mut data, &record.fields)?;
+                    data.push(b'\n');
+                }
+                Ok(RequestBody::Raw {
+                    content_type: "application/x-ndjson".to_string(),
+                    data,
+                })
+            }
+        }
+    }
+
+    fn render_template(template: &serde_json::Value, records: &serde_json::Value) -> serde_json::Value {
+        match template {
+            serde_json::Value::String(s) if s == RECORDS_PLACEHOLDER => records.clone(),
+            serde_json::Value::Array(items) => serde_json::Value::Array(
+                items.iter().map(|item| Self::render_template(item, records)).collect(),
+            ),
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), Self::render_template(v, records)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    fn record_value(record: &DataRecord) -> serde_json::Value {
+        serde_json::Value::Object(
+            record.fields.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+        )
+    }
+
+    /// 每個失敗批次寫成一行 `{"batch", "url", "error", "failed_at", "records"}`
+    fn write_dead_letter(&self, index: usize, batch: &[DataRecord], error: &EtlError) -> Result<()> {
+        if let Some(parent) = self.dead_letter_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let entry = serde_json::json!({
+            "batch": index,
+            "url": self.url,
+            "error": error.to_string(),
+            "failed_at": chrono::Utc::now().to_rfc3339(),
+            "records": batch.iter().map(Self::record_value).collect::<Vec<_>>(),
+        });
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.dead_letter_path)?;
+        serde_json::to_writer(&mut file, &entry)?;
+        file.write_all(b"\n")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{record, sample_records, TestResponse, TestServer};
+
+    fn writer(destination: serde_json::Value, batch_size: usize) -> Result<ApiWriter> {
+        let mut config = serde_json::json!({ "type": "api" });
+        config.as_object_mut().unwrap().extend(destination.as_object().unwrap().clone());
+        let destination: OutputDestination = serde_json::from_value(config).unwrap();
+        let options = OutputOptions {
+            batch_size: Some(batch_size),
+            max_file_size: None,
+            split_by_field: None,
+            filename_template: None,
+        };
+        ApiWriter::from_config(&destination, Some(&options))
+    }
+
+    #[tokio::test]
+    async fn ndjson_batches_are_sent_one_record_per_line() {
+        let server = TestServer::start(|_| TestResponse::new(202, "")).await;
+        let writer = writer(
+            serde_json::json!({ "url": format!("{}/ingest", server.url), "method": "put", "batch_format": "ndjson" }),
+            2,
+        )
+        .unwrap();
+
+        let summary = writer.write_records(&sample_records()).await.unwrap();
+
+        assert_eq!((summary.batches, summary.sent_records, summary.failed_batches), (2, 3, 0));
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[0].header("content-type"), Some("application/x-ndjson"));
+        let lines: Vec<serde_json::Value> =
+            requests[0].body_text().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(
+            lines,
+            vec![serde_json::json!({ "id": 1, "name": "item 1" }), serde_json::json!({ "id": 2, "name": "item 2" })]
+        );
+        assert!(requests[0].body_text().ends_with('\n'));
+        assert_eq!(requests[1].body_text().lines().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn body_template_replaces_every_records_placeholder() {
+        let server = TestServer::start(|_| TestResponse::new(200, "")).await;
+        let writer = writer(
+            serde_json::json!({
+                "url": server.url,
+                "body_template": { "source": "etl", "data": { "items": "{records}" }, "copies": ["{records}", "{other}"] }
+            }),
+            10,
+        )
+        .unwrap();
+
+        writer.write_records(&[record(serde_json::json!({ "id": 7 }))]).await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.header("content-type"), Some("application/json"));
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "source": "etl",
+                "data": { "items": [{ "id": 7 }] },
+                "copies": [[{ "id": 7 }], "{other}"]
+            })
+        );
+    }
+
+    #[test]
+    fn body_template_is_rejected_with_ndjson() {
+        let result = writer(
+            serde_json::json!({
+                "url": "http://localhost/ingest",
+                "batch_format": "ndjson",
+                "body_template": { "items": "{records}" }
+            }),
+            10,
+        );
+        match result {
+            Err(EtlError::ConfigError(message)) => assert!(message.contains("json_array"), "{}", message),
+            Err(other) => panic!("expected ConfigError, got {:?}", other),
+            Ok(_) => panic!("expected ConfigError"),
+        }
+    }
+
+    #[tokio::test]
+    async fn failed_batches_are_written_to_the_dead_letter_file() {
+        let server = TestServer::start(|request| {
+            if request.body_text().contains("item 2") {
+                TestResponse::new(422, "invalid")
+            } else {
+                TestResponse::new(200, "")
+            }
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let dead_letter = dir.path().join("failed/dead_letter.ndjson");
+        let url = format!("{}/ingest", server.url);
+        let writer = writer(
+            serde_json::json!({ "url": url, "dead_letter_path": dead_letter.display().to_string() }),
+            1,
+        )
+        .unwrap();
+
+        let summary = writer.write_records(&sample_records()).await.unwrap();
+
+        assert_eq!((summary.batches, summary.sent_records), (3, 2));
+        assert_eq!((summary.failed_batches, summary.failed_records), (1, 1));
+        assert_eq!(writer.dead_letter_path(), dead_letter);
+        let content = std::fs::read_to_string(&dead_letter).unwrap();
+        let lines: Vec<serde_json::Value> = content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 1);
+        let entry = &lines[0];
+        assert_eq!(entry["batch"], 1);
+        assert_eq!(entry["url"], url);
+        assert!(entry["error"].as_str().unwrap().contains("422"), "{}", entry["error"]);
+        assert!(chrono::DateTime::parse_from_rfc3339(entry["failed_at"].as_str().unwrap()).is_ok());
+        assert_eq!(entry["records"], serde_json::json!([{ "id": 2, "name": "item 2" }]));
+    }
+}
@@ -1,42 +1,188 @@
-use crate::utils::error::Result;
+use crate::config::settings::{CompressionOptions, CompressionType, ZipMethod};
+use crate::utils::error::{EtlError, Result};
+use crate::utils::helpers::resolve_secret;
+use chrono::{Datelike, Timelike};
+use flate2::{Compression, GzBuilder};
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{BufWriter, Read, Write};
 use std::path::Path;
-use zip::write::{ExtendedFileOptions, FileOptions};
-use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipWriter};
 
-pub struct Archiver;
+const DEFAULT_FILE_PERMISSIONS: u32 = 0o644;
+
+pub struct Archiver {
+    options: CompressionOptions,
+}
+
+impl Default for Archiver {
+    fn default() -> Self {
+        Self::new(CompressionOptions::default())
+    }
+}
 
 impl Archiver {
+    pub fn new(options: CompressionOptions) -> Self {
+        Self { options }
+    }
+
+    /// 依照壓縮類型輸出檔案，單檔格式（gzip/bzip2/zstd）只接受一個檔案；密碼僅支援 ZIP
+    pub fn write_archive<P: AsRef<Path>>(
+        &self,
+        output_path: P,
+        files: Vec<(String, Vec<u8>)>,
+        compression: &CompressionType,
+    ) -> Result<()> {
+        if self.options.password.is_some() && !matches!(compression, CompressionType::Zip) {
+            return Err(EtlError::ConfigError(format!(
+                "{:?} compression does not support passwords; use zip for encrypted output",
+                compression
+            )));
+        }
+
+        match compression {
+            CompressionType::Zip => self.create_zip(output_path, files),
+            CompressionType::Tar => self.create_tar(output_path, files, false),
+            CompressionType::TarGz => self.create_tar(output_path, files, true),
+            CompressionType::Gzip | CompressionType::Bzip2 | CompressionType::Zstd => {
+                let (name, content) = Self::single_file(files, compression)?;
+                self.compress_single(output_path, &name, &content, compression)
+            }
+        }
+    }
+
     pub fn create_zip<P: AsRef<Path>>(
+        &self,
         output_path: P,
         files: Vec<(String, Vec<u8>)>,
     ) -> Result<()> {
         let file = File::create(output_path)?;
-        let mut zip = ZipWriter::new(file);
+        let mut zip = ZipWriter::new(BufWriter::new(file));
 
-        let options: FileOptions<'_, ExtendedFileOptions> = FileOptions::default()
-            // .compression_method(zip::CompressionMethod::Deflated)
-            .compression_method(zip::CompressionMethod::Ppmd)
-            // .compression_level()
-            .unix_permissions(0o755);
+        let method = match self.options.method.unwrap_or_default() {
+            ZipMethod::Stored => CompressionMethod::Stored,
+            ZipMethod::Deflate => CompressionMethod::Deflated,
+            ZipMethod::Bzip2 => CompressionMethod::Bzip2,
+            ZipMethod::Zstd => CompressionMethod::Zstd,
+        };
+        let level = match method {
+            CompressionMethod::Stored => None,
+            _ => self.options.level,
+        };
+
+        let password = self.options.password.as_ref().map(resolve_secret).transpose()?;
+
+        let mut options = SimpleFileOptions::default()
+            .compression_method(method)
+            .compression_level(level)
+            .last_modified_time(Self::zip_timestamp())
+            .unix_permissions(self.permissions());
+        if let Some(password) = password.as_deref() {
+            options = options.with_aes_encryption(AesMode::Aes256, password);
+        }
 
         for (name, content) in files {
-            zip.start_file(name, options.clone())?;
+            zip.start_file(name, options)?;
             zip.write_all(&content)?;
         }
 
-        zip.finish()?;
+        zip.finish()?.flush()?;
         Ok(())
     }
 
-    pub fn extract_zip<P: AsRef<Path>>(zip_path: P) -> Result<Vec<(String, Vec<u8>)>> {
+    pub fn create_tar<P: AsRef<Path>>(
+        &self,
+        output_path: P,
+        files: Vec<(String, Vec<u8>)>,
+        gzip: bool,
+    ) -> Result<()> {
+        let file = BufWriter::new(File::create(output_path)?);
+
+        if gzip {
+            let encoder = GzBuilder::new()
+                .mtime(Self::unix_timestamp() as u32)
+                .write(file, self.gzip_level()?);
+            self.append_tar_entries(encoder, files)?.finish()?.flush()?;
+        } else {
+            self.append_tar_entries(file, files)?.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn append_tar_entries<W: Write>(&self, writer: W, files: Vec<(String, Vec<u8>)>) -> Result<W> {
+        let mut builder = tar::Builder::new(writer);
+        let mtime = Self::unix_timestamp();
+
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(self.permissions());
+            header.set_mtime(mtime);
+            header.set_entry_type(tar::EntryType::Regular);
+            builder.append_data(&mut header, name, content.as_slice())?;
+        }
+
+        Ok(builder.into_inner()?)
+    }
+
+    fn compress_single<P: AsRef<Path>>(
+        &self,
+        output_path: P,
+        name: &str,
+        content: &[u8],
+        compression: &CompressionType,
+    ) -> Result<()> {
+        let file = BufWriter::new(File::create(output_path)?);
+
+        match compression {
+            CompressionType::Gzip => {
+                let mut encoder = GzBuilder::new()
+                    .filename(name)
+                    .mtime(Self::unix_timestamp() as u32)
+                    .write(file, self.gzip_level()?);
+                encoder.write_all(content)?;
+                encoder.finish()?.flush()?;
+            }
+            CompressionType::Bzip2 => {
+                let level = match self.options.level {
+                    Some(level @ 1..=9) => bzip2::Compression::new(level as u32),
+                    Some(level) => return Err(Self::invalid_level("bzip2", level, "1-9")),
+                    None => bzip2::Compression::default(),
+                };
+                let mut encoder = bzip2::write::BzEncoder::new(file, level);
+                encoder.write_all(content)?;
+                encoder.finish()?.flush()?;
+            }
+            CompressionType::Zstd => {
+                let level = match self.options.level {
+                    Some(level @ 1..=22) => level as i32,
+                    Some(level) => return Err(Self::invalid_level("zstd", level, "1-22")),
+                    None => zstd::DEFAULT_COMPRESSION_LEVEL,
+                };
+                let mut encoder = zstd::Encoder::new(file, level)?;
+                encoder.write_all(content)?;
+                encoder.finish()?.flush()?;
+            }
+            _ => unreachable!("multi-file formats are handled by write_archive"),
+        }
+
+        Ok(())
+    }
+
+    pub fn extract_zip<P: AsRef<Path>>(
+        zip_path: P,
+        password: Option<&str>,
+    ) -> Result<Vec<(String, Vec<u8>)>> {
         let file = File::open(zip_path)?;
         let mut archive = zip::ZipArchive::new(file)?;
         let mut files = Vec::new();
 
         for i in 0..archive.len() {
-            let mut file = archive.by_index(i)?;
+            let mut file = match password {
+                Some(password) => archive.by_index_decrypt(i, password.as_bytes())?,
+                None => archive.by_index(i)?,
+            };
             let name = file.name().to_string();
 
             let mut contents = Vec::new();
@@ -47,4 +193,262 @@ impl Archiver {
 
         Ok(files)
     }
+
+    fn single_file(
+        mut files: Vec<(String, Vec<u8>)>,
+        compression: &CompressionType,
+    ) -> Result<(String, Vec<u8>)> {
+        if files.len() != 1 {
+            return Err(EtlError::ConfigError(format!(
+                "{:?} compression holds exactly one file but {} were given; use tar_gz or zip instead",
+                compression,
+                files.len()
+            )));
+        }
+        Ok(files.remove(0))
+    }
+
+    fn gzip_level(&self) -> Result<Compression> {
+        match self.options.level {
+            Some(level @ 0..=9) => Ok(Compression::new(level as u32)),
+            Some(level) => Err(Self::invalid_level("gzip", level, "0-9")),
+            None => Ok(Compression::default()),
+        }
+    }
+
+    fn permissions(&self) -> u32 {
+        self.options.file_permissions.unwrap_or(DEFAULT_FILE_PERMISSIONS)
+    }
+
+    fn invalid_level(format: &str, level: i64, range: &str) -> EtlError {
+        EtlError::ConfigError(format!(
+            "Invalid {} compression level {}: expected {}",
+            format, level, range
+        ))
+    }
+
+    fn unix_timestamp() -> u64 {
+        chrono::Utc::now().timestamp().max(0) as u64
+    }
+
+    /// ZIP 使用 MS-DOS 本地時間格式，超出範圍（1980 年以前）時退回預設值
+    fn zip_timestamp() -> zip::DateTime {
+        let now = chrono::Local::now();
+        zip::DateTime::from_date_and_time(
+            now.year() as u16,
+            now.month() as u8,
+            now.day() as u8,
+            now.hour() as u8,
+            now.minute() as u8,
+            now.second() as u8,
+        )
+        .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::{FileFormat, SecretRef};
+    use crate::extractors::file_reader::FileReader;
+    use std::io::Cursor;
+
+    fn archiver(method: Option<ZipMethod>, level: Option<i64>) -> Archiver {
+        Archiver::new(CompressionOptions { method, level, ..Default::default() })
+    }
+
+    fn one_file() -> Vec<(String, Vec<u8>)> {
+        vec![("data.csv".to_string(), b"id,name\n1,a\n".to_vec())]
+    }
+
+    fn two_files() -> Vec<(String, Vec<u8>)> {
+        vec![
+            ("a.json".to_string(), b"[1]".to_vec()),
+            ("b.json".to_string(), b"[2]".to_vec()),
+        ]
+    }
+
+    #[test]
+    fn gzip_keeps_the_original_filename() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.csv.gz");
+        archiver(None, Some(9)).write_archive(&path, one_file(), &CompressionType::Gzip).unwrap();
+
+        let mut decoder = flate2::read::GzDecoder::new(File::open(&path).unwrap());
+        let mut content = Vec::new();
+        decoder.read_to_end(&mut content).unwrap();
+        assert_eq!(content, b"id,name\n1,a\n");
+        assert_eq!(decoder.header().unwrap().filename(), Some(&b"data.csv"[..]));
+    }
+
+    #[test]
+    fn bzip2_and_zstd_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let bz = dir.path().join("data.csv.bz2");
+        archiver(None, Some(1)).write_archive(&bz, one_file(), &CompressionType::Bzip2).unwrap();
+        let mut content = Vec::new();
+        bzip2::read::BzDecoder::new(File::open(&bz).unwrap()).read_to_end(&mut content).unwrap();
+        assert_eq!(content, b"id,name\n1,a\n");
+
+        let zst = dir.path().join("data.csv.zst");
+        archiver(None, Some(22)).write_archive(&zst, one_file(), &CompressionType::Zstd).unwrap();
+        let content = zstd::decode_all(File::open(&zst).unwrap()).unwrap();
+        assert_eq!(content, b"id,name\n1,a\n");
+    }
+
+    #[test]
+    fn plain_tar_holds_every_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.tar");
+        archiver(None, None).write_archive(&path, two_files(), &CompressionType::Tar).unwrap();
+
+        let mut archive = tar::Archive::new(File::open(&path).unwrap());
+        let mut entries = Vec::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            assert_eq!(entry.header().mode().unwrap(), DEFAULT_FILE_PERMISSIONS);
+            let mut content = String::new();
+            entry.read_to_string(&mut content).unwrap();
+            entries.push((name, content));
+        }
+        assert_eq!(
+            entries,
+            vec![
+                ("a.json".to_string(), "[1]".to_string()),
+                ("b.json".to_string(), "[2]".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn zip_uses_the_configured_method() {
+        let dir = tempfile::tempdir().unwrap();
+        let cases = [
+            (ZipMethod::Stored, CompressionMethod::Stored),
+            (ZipMethod::Bzip2, CompressionMethod::Bzip2),
+            (ZipMethod::Zstd, CompressionMethod::Zstd),
+        ];
+
+        for (method, expected) in cases {
+            let path = dir.path().join(format!("{:?}.zip", method));
+            archiver(Some(method), None).write_archive(&path, two_files(), &CompressionType::Zip).unwrap();
+
+            let bytes = std::fs::read(&path).unwrap();
+            let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+            assert_eq!(archive.by_index(0).unwrap().compression(), expected);
+            assert_eq!(Archiver::extract_zip(&path, None).unwrap(), two_files());
+        }
+    }
+
+    #[test]
+    fn out_of_range_levels_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let cases = [
+            (CompressionType::Gzip, 10, "gzip"),
+            (CompressionType::TarGz, -1, "gzip"),
+            (CompressionType::Bzip2, 0, "bzip2"),
+            (CompressionType::Zstd, 23, "zstd"),
+        ];
+
+        for (compression, level, format) in cases {
+            let path = dir.path().join(format!("out.{}", compression.extension()));
+            let err = archiver(None, Some(level))
+                .write_archive(&path, one_file(), &compression)
+                .unwrap_err();
+            match err {
+                EtlError::ConfigError(message) => {
+                    let expected = format!("Invalid {} compression level {}", format, level);
+                    assert!(message.contains(&expected), "{}", message);
+                }
+                other => panic!("expected ConfigError, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn single_file_formats_require_exactly_one_file() {
+        let dir = tempfile::tempdir().unwrap();
+        for compression in [CompressionType::Gzip, CompressionType::Bzip2, CompressionType::Zstd] {
+            let path = dir.path().join(format!("out.{}", compression.extension()));
+            let err = archiver(None, None).write_archive(&path, two_files(), &compression).unwrap_err();
+            match err {
+                EtlError::ConfigError(message) => assert!(message.contains("exactly one file but 2 were given")),
+                other => panic!("expected ConfigError, got {:?}", other),
+            }
+        }
+    }
+
+    fn encrypted(dir: &Path, password: &str) -> Archiver {
+        let secret = dir.join("zip_password");
+        std::fs::write(&secret, format!("{}\n", password)).unwrap();
+        Archiver::new(CompressionOptions {
+            password: Some(SecretRef::File(secret.to_string_lossy().into_owned())),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn password_is_rejected_for_non_zip_formats() {
+        let dir = tempfile::tempdir().unwrap();
+        let archiver = encrypted(dir.path(), "s3cret");
+        let formats = [
+            CompressionType::Tar,
+            CompressionType::TarGz,
+            CompressionType::Gzip,
+            CompressionType::Bzip2,
+            CompressionType::Zstd,
+        ];
+
+        for compression in formats {
+            let path = dir.path().join(format!("out.{}", compression.extension()));
+            let err = archiver.write_archive(&path, one_file(), &compression).unwrap_err();
+            match err {
+                EtlError::ConfigError(message) => assert!(message.contains("does not support passwords")),
+                other => panic!("expected ConfigError, got {:?}", other),
+            }
+            assert!(!path.exists());
+        }
+    }
+
+    #[tokio::test]
+    async fn aes_zip_round_trips_through_extract_and_file_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.zip");
+        encrypted(dir.path(), "s3cret")
+            .write_archive(&path, one_file(), &CompressionType::Zip)
+            .unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        assert!(archive.by_index_raw(0).unwrap().encrypted());
+
+        assert_eq!(Archiver::extract_zip(&path, Some("s3cret")).unwrap(), one_file());
+
+        let format = FileFormat::Zip {
+            extract_path: None,
+            target_files: vec!["*.csv".to_string()],
+            password: Some(SecretRef::File(dir.path().join("zip_password").to_string_lossy().into_owned())),
+        };
+        let records = FileReader::new().read_file(path.to_str().unwrap(), format).await.unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].fields["name"], "a");
+    }
+
+    #[test]
+    fn wrong_or_missing_password_is_an_auth_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.zip");
+        encrypted(dir.path(), "s3cret")
+            .write_archive(&path, one_file(), &CompressionType::Zip)
+            .unwrap();
+
+        for password in [Some("wrong"), None] {
+            match Archiver::extract_zip(&path, password) {
+                Err(EtlError::AuthError(_)) => {}
+                other => panic!("expected AuthError for {:?}, got {:?}", password, other),
+            }
+        }
+    }
 }
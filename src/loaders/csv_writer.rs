@@ -1,16 +1,34 @@
 use crate::models::data_types::DataRecord;
 use crate::utils::error::Result;
-use csv::WriterBuilder;
+use csv::{QuoteStyle, WriterBuilder};
+use std::collections::BTreeSet;
 use std::fs::File;
+use std::io::Write;
 use std::path::Path;
 
 pub struct CsvWriter {
     delimiter: u8,
+    tsv: bool,
 }
 
 impl CsvWriter {
     pub fn new(delimiter: u8) -> Self {
-        Self { delimiter }
+        Self { delimiter, tsv: false }
+    }
+
+    /// TSV 不使用引號，欄位中的 tab、換行與反斜線以跳脫字元表示
+    pub fn tsv() -> Self {
+        Self { delimiter: b'\t', tsv: true }
+    }
+
+    /// 取得所有記錄欄位名稱的聯集，依字母排序
+    pub fn headers_from_records(records: &[DataRecord]) -> Vec<String> {
+        records
+            .iter()
+            .flat_map(|record| record.fields.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
     }
 
     pub fn write_records<P: AsRef<Path>>(
@@ -20,23 +38,36 @@ impl CsvWriter {
         headers: &[String],
     ) -> Result<()> {
         let file = File::create(path)?;
+        self.write_to(file, records, headers)
+    }
+
+    pub fn write_to<W: Write>(
+        &self,
+        output: W,
+        records: &[DataRecord],
+        headers: &[String],
+    ) -> Result<()> {
+        let quote_style = if self.tsv { QuoteStyle::Never } else { QuoteStyle::Necessary };
         let mut writer = WriterBuilder::new()
             .delimiter(self.delimiter)
-            .from_writer(file);
+            .quote_style(quote_style)
+            .from_writer(output);
 
         // 寫入標題
-        writer.write_record(headers)?;
+        let header_row: Vec<String> = headers.iter().map(|h| self.escape(h)).collect();
+        writer.write_record(&header_row)?;
 
         // 寫入資料
         for record in records {
             let row: Vec<String> = headers
                 .iter()
                 .map(|header| {
-                    record.fields
-                        .get(header)
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("")
-                        .to_string()
+                    let value = match record.fields.get(header) {
+                        None | Some(serde_json::Value::Null) => String::new(),
+                        Some(serde_json::Value::String(s)) => s.clone(),
+                        Some(other) => other.to_string(),
+                    };
+                    self.escape(&value)
                 })
                 .collect();
 
@@ -46,4 +77,22 @@ impl CsvWriter {
         writer.flush()?;
         Ok(())
     }
+
+    fn escape(&self, value: &str) -> String {
+        if !self.tsv {
+            return value.to_string();
+        }
+
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.chars() {
+            match c {
+                '\\' => escaped.push_str("\\\\"),
+                '\t' => escaped.push_str("\\t"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
 }
@@ -0,0 +1,191 @@
+use crate::config::settings::{CompressionOptions, CompressionType, OutputDestination, OutputFormat};
+use crate::loaders::archiver::Archiver;
+use crate::loaders::csv_writer::CsvWriter;
+use crate::models::data_types::DataRecord;
+use crate::utils::error::{EtlError, Result};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// 將記錄寫入本地檔案，設定 `compress` 時交由 `Archiver` 壓縮
+pub struct LocalFileWriter {
+    path: PathBuf,
+    format: OutputFormat,
+    compress: Option<CompressionType>,
+    compression_options: CompressionOptions,
+}
+
+impl LocalFileWriter {
+    /// 由 `OutputDestination::LocalFile` 建立
+    pub fn from_config(destination: &OutputDestination, format: &OutputFormat) -> Result<Self> {
+        let OutputDestination::LocalFile { path, compress, compression_options } = destination else {
+            return Err(EtlError::ConfigError(
+                "LocalFileWriter requires a local_file output destination".to_string(),
+            ));
+        };
+        extension(format)?;
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            format: format.clone(),
+            compress: compress.clone(),
+            compression_options: compression_options.clone().unwrap_or_default(),
+        })
+    }
+
+    /// 回傳實際寫入的檔案路徑；壓縮時路徑未含壓縮副檔名會自動補上
+    pub fn write_records(&self, records: &[DataRecord]) -> Result<PathBuf> {
+        let mut content = Vec::new();
+        serialize(&self.format, records, &mut content)?;
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let written = match &self.compress {
+            None => {
+                std::fs::write(&self.path, content)?;
+                self.path.clone()
+            }
+            Some(compression) => {
+                let (archive_path, entry_name) = self.archive_paths(compression)?;
+                Archiver::new(self.compression_options.clone()).write_archive(
+                    &archive_path,
+                    vec![(entry_name, content)],
+                    compression,
+                )?;
+                archive_path
+            }
+        };
+
+        info!("Wrote {} records to {}", records.len(), written.display());
+        Ok(written)
+    }
+
+    /// 例如 `out/data.csv` 以 tar_gz 壓縮時輸出 `out/data.csv.tar.gz`，內含 `data.csv`
+    fn archive_paths(&self, compression: &CompressionType) -> Result<(PathBuf, String)> {
+        let file_name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "output".to_string());
+        let suffix = format!(".{}", compression.extension());
+
+        let (archive_path, entry_name) = match file_name.strip_suffix(&suffix) {
+            Some(stem) => (self.path.clone(), stem.to_string()),
+            None => (append_extension(&self.path, compression.extension()), file_name),
+        };
+        let format_extension = extension(&self.format)?;
+        let entry_name = if Path::new(&entry_name).extension().is_some() {
+            entry_name
+        } else {
+            format!("{}.{}", entry_name, format_extension)
+        };
+        Ok((archive_path, entry_name))
+    }
+}
+
+/// 依輸出格式序列化記錄，目前支援 csv 與 json
+pub fn serialize<W: Write>(format: &OutputFormat, records: &[DataRecord], mut output: W) -> Result<()> {
+    match format {
+        OutputFormat::Csv { delimiter, .. } => {
+            let delimiter = delimiter.unwrap_or(',');
+            if !delimiter.is_ascii() {
+                return Err(EtlError::ConfigError(format!(
+                    "CSV delimiter must be a single ASCII character, got '{}'",
+                    delimiter
+                )));
+            }
+            let headers = CsvWriter::headers_from_records(records);
+            CsvWriter::new(delimiter as u8).write_to(&mut output, records, &headers)?;
+        }
+        OutputFormat::Json { pretty_print } => {
+            let values: Vec<&HashMap<String, serde_json::Value>> =
+                records.iter().map(|record| &record.fields).collect();
+            if pretty_print.unwrap_or(false) {
+                serde_json::to_writer_pretty(&mut output, &values)?;
+            } else {
+                serde_json::to_writer(&mut output, &values)?;
+            }
+        }
+        other => {
+            return Err(EtlError::ConfigError(format!(
+                "File output supports csv and json formats, got {:?}",
+                other
+            )))
+        }
+    }
+    output.flush()?;
+    Ok(())
+}
+
+fn extension(format: &OutputFormat) -> Result<&'static str> {
+    match format {
+        OutputFormat::Csv { .. } => Ok("csv"),
+        OutputFormat::Json { .. } => Ok("json"),
+        other => Err(EtlError::ConfigError(format!(
+            "File output supports csv and json formats, got {:?}",
+            other
+        ))),
+    }
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".");
+    path.push(extension);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::sample_records;
+    use std::io::Read;
+
+    fn writer(path: &Path, compress: CompressionType) -> LocalFileWriter {
+        let destination = OutputDestination::LocalFile {
+            path: path.to_string_lossy().to_string(),
+            compress: Some(compress),
+            compression_options: Some(CompressionOptions { level: Some(9), ..Default::default() }),
+        };
+        let format = OutputFormat::Csv { delimiter: None, quote_char: None, headers: None };
+        LocalFileWriter::from_config(&destination, &format).unwrap()
+    }
+
+    const EXPECTED_CSV: &str = "id,name\n1,item 1\n2,item 2\n3,item 3\n";
+
+    #[test]
+    fn zip_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = writer(&dir.path().join("data.csv"), CompressionType::Zip)
+            .write_records(&sample_records())
+            .unwrap();
+
+        assert_eq!(written, dir.path().join("data.csv.zip"));
+        let files = Archiver::extract_zip(&written, None).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, "data.csv");
+        assert_eq!(String::from_utf8(files[0].1.clone()).unwrap(), EXPECTED_CSV);
+    }
+
+    #[test]
+    fn tar_gz_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let written = writer(&dir.path().join("export.tar.gz"), CompressionType::TarGz)
+            .write_records(&sample_records())
+            .unwrap();
+
+        assert_eq!(written, dir.path().join("export.tar.gz"));
+        let file = std::fs::File::open(&written).unwrap();
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+        let mut entries = archive.entries().unwrap();
+        let mut entry = entries.next().unwrap().unwrap();
+        assert_eq!(entry.path().unwrap().to_string_lossy(), "export.csv");
+        let mut content = String::new();
+        entry.read_to_string(&mut content).unwrap();
+        assert_eq!(content, EXPECTED_CSV);
+        assert!(entries.next().is_none());
+    }
+}
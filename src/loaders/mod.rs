@@ -1,2 +1,7 @@
 pub mod csv_writer;
-pub mod archiver;
\ No newline at end of file
+pub mod archiver;
+pub mod file_writer;
+pub mod api_writer;
+pub mod s3_writer;
+pub mod sqlite_writer;
+pub mod surreal_writer;
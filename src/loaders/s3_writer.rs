@@ -0,0 +1,411 @@
+use crate::config::settings::{OutputDestination, OutputFormat, ServerSideEncryption};
+use crate::extractors::api_client::RequestBody;
+use crate::extractors::payload::xml_to_json;
+use crate::extractors::s3::S3Client;
+use crate::loaders::file_writer;
+use crate::models::data_types::DataRecord;
+use crate::utils::error::{EtlError, Result};
+use crate::utils::template::TemplateContext;
+use std::collections::HashMap;
+use std::io::{BufWriter, Read, Seek, SeekFrom};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+const MIB: u64 = 1024 * 1024;
+const DEFAULT_PART_SIZE_MB: u64 = 8;
+/// S3 規定除最後一段外，每個分段至少 5 MiB
+const MIN_PART_SIZE_MB: u64 = 5;
+
+/// 將記錄序列化為單一物件上傳到 S3，超過分段大小時使用 multipart upload
+pub struct S3Writer {
+    client: Arc<S3Client>,
+    bucket: String,
+    key: String,
+    format: OutputFormat,
+    content_type: String,
+    /// 建立物件時送出的 metadata 與加密標頭
+    headers: HashMap<String, String>,
+    part_size: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct S3UploadSummary {
+    pub key: String,
+    pub bytes: u64,
+    /// 單一 PUT 時為 1
+    pub parts: usize,
+}
+
+impl S3Writer {
+    /// 由 `OutputDestination::S3` 建立，key 與 metadata 中的模板在此展開
+    pub fn from_config(
+        destination: &OutputDestination,
+        format: &OutputFormat,
+        variables: &HashMap<String, String>,
+    ) -> Result<Self> {
+        let OutputDestination::S3 {
+            bucket,
+            key,
+            region,
+            credentials,
+            endpoint,
+            path_style,
+            content_type,
+            metadata,
+            server_side_encryption,
+            part_size_mb,
+            retry,
+        } = destination
+        else {
+            return Err(EtlError::ConfigError(
+                "S3Writer requires an s3 output destination".to_string(),
+            ));
+        };
+
+        let default_content_type = match format {
+            OutputFormat::Csv { .. } => "text/csv",
+            OutputFormat::Json { .. } => "application/json",
+            other => {
+                return Err(EtlError::ConfigError(format!(
+                    "S3 output supports csv and json formats, got {:?}",
+                    other
+                )))
+            }
+        };
+        let part_size_mb = part_size_mb.unwrap_or(DEFAULT_PART_SIZE_MB);
+        if part_size_mb < MIN_PART_SIZE_MB {
+            return Err(EtlError::ConfigError(format!(
+                "part_size_mb must be at least {}, got {}",
+                MIN_PART_SIZE_MB, part_size_mb
+            )));
+        }
+
+        let context = TemplateContext::new(variables.clone());
+        let now = chrono::Utc::now();
+        let render = |value: &str| -> Result<String> {
+            Ok(context
+                .render(value)?
+                .replace("{timestamp}", &now.format("%Y%m%dT%H%M%SZ").to_string())
+                .replace("{date}", &now.format("%Y-%m-%d").to_string()))
+        };
+
+        let mut headers = HashMap::new();
+        for (name, value) in metadata.iter().flatten() {
+            headers.insert(format!("x-amz-meta-{}", name.to_lowercase()), render(value)?);
+        }
+        match server_side_encryption {
+            Some(ServerSideEncryption::Aes256) => {
+                headers.insert("x-amz-server-side-encryption".to_string(), "AES256".to_string());
+            }
+            Some(ServerSideEncryption::AwsKms { kms_key_id }) => {
+                headers.insert("x-amz-server-side-encryption".to_string(), "aws:kms".to_string());
+                if let Some(kms_key_id) = kms_key_id {
+                    headers.insert(
+                        "x-amz-server-side-encryption-aws-kms-key-id".to_string(),
+                        kms_key_id.clone(),
+                    );
+                }
+            }
+            None => {}
+        }
+
+        Ok(Self {
+            client: Arc::new(
+                S3Client::new(bucket, region, credentials.clone(), endpoint.as_deref(), *path_style)?
+                    .with_retry(retry.clone()),
+            ),
+            bucket: bucket.clone(),
+            key: render(key)?.trim_start_matches('/').to_string(),
+            format: format.clone(),
+            content_type: content_type.clone().unwrap_or_else(|| default_content_type.to_string()),
+            headers,
+            part_size: part_size_mb * MIB,
+        })
+    }
+
+    pub async fn write_records(&self, records: &[DataRecord]) -> Result<S3UploadSummary> {
+        let mut file = tempfile::Builder::new().prefix("etl-s3-upload-").tempfile()?;
+        file_writer::serialize(&self.format, records, BufWriter::new(file.as_file_mut()))?;
+        let bytes = file.as_file().metadata()?.len();
+
+        let parts = if bytes <= self.part_size {
+            let mut data = Vec::with_capacity(bytes as usize);
+            file.reopen()?.read_to_end(&mut data)?;
+            self.client
+                .send("PUT", &self.key, &[], Some(self.headers.clone()), Some(self.raw_body(data)))
+                .await?;
+            1
+        } else {
+            self.multipart_upload(file.reopen()?, bytes).await?
+        };
+
+        info!("Uploaded {} records ({} bytes) to s3://{}/{}", records.len(), bytes, self.bucket, self.key);
+        Ok(S3UploadSummary { key: self.key.clone(), bytes, parts })
+    }
+
+    /// 建立 upload 後逐段上傳；任何一步失敗或 future 被取消都會中止 upload，避免殘留未完成的分段持續計費
+    async fn multipart_upload(&self, mut file: std::fs::File, bytes: u64) -> Result<usize> {
+        let mut headers = self.headers.clone();
+        headers.insert("Content-Type".to_string(), self.content_type.clone());
+        let response = self.client.send("POST", &self.key, &[("uploads", "")], Some(headers), None).await?;
+        let document = Self::parse_xml(&response.text().await?)?;
+        let upload_id = document["InitiateMultipartUploadResult"]["UploadId"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| EtlError::ParseError("CreateMultipartUpload response has no UploadId".to_string()))?;
+        debug!("Started multipart upload {} for s3://{}/{}", upload_id, self.bucket, self.key);
+
+        let mut guard = AbortGuard {
+            upload: Some(PendingUpload {
+                client: self.client.clone(),
+                bucket: self.bucket.clone(),
+                key: self.key.clone(),
+                upload_id: upload_id.clone(),
+            }),
+        };
+        let result = self.upload_parts(&mut file, bytes, &upload_id).await;
+        let upload = guard.upload.take();
+        if let (Err(_), Some(upload)) = (&result, upload) {
+            upload.abort().await;
+        }
+        result
+    }
+
+    async fn upload_parts(&self, file: &mut std::fs::File, bytes: u64, upload_id: &str) -> Result<usize> {
+        let mut etags = Vec::new();
+        file.seek(SeekFrom::Start(0))?;
+        for (index, offset) in (0..bytes).step_by(self.part_size as usize).enumerate() {
+            let part_number = (index + 1).to_string();
+            let mut data = Vec::with_capacity(self.part_size.min(bytes - offset) as usize);
+            Read::by_ref(file).take(self.part_size).read_to_end(&mut data)?;
+
+            let response = self
+                .client
+                .send(
+                    "PUT",
+                    &self.key,
+                    &[("partNumber", &part_number), ("uploadId", upload_id)],
+                    None,
+                    Some(self.raw_body(data)),
+                )
+                .await?;
+            let etag = response
+                .headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+                .ok_or_else(|| EtlError::ApiError(format!("Upload of part {} returned no ETag", part_number)))?;
+            debug!("Uploaded part {} of s3://{}/{}", part_number, self.bucket, self.key);
+            etags.push((part_number, etag));
+        }
+
+        let parts: String = etags
+            .iter()
+            .map(|(number, etag)| {
+                format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    number,
+                    quick_xml::escape::escape(etag.as_str())
+                )
+            })
+            .collect();
+        let body = format!("<CompleteMultipartUpload>{}</CompleteMultipartUpload>", parts);
+        let response = self
+            .client
+            .send(
+                "POST",
+                &self.key,
+                &[("uploadId", upload_id)],
+                None,
+                Some(RequestBody::Raw { content_type: "application/xml".to_string(), data: body.into_bytes() }),
+            )
+            .await?;
+        // CompleteMultipartUpload 可能在 200 回應中回傳錯誤
+        let document = Self::parse_xml(&response.text().await?)?;
+        if let Some(error) = document.get("Error") {
+            return Err(EtlError::ApiError(format!(
+                "CompleteMultipartUpload failed: {} {}",
+                error["Code"].as_str().unwrap_or_default(),
+                error["Message"].as_str().unwrap_or_default()
+            )));
+        }
+        Ok(etags.len())
+    }
+
+    fn raw_body(&self, data: Vec<u8>) -> RequestBody {
+        RequestBody::Raw { content_type: self.content_type.clone(), data }
+    }
+
+    fn parse_xml(xml: &str) -> Result<serde_json::Value> {
+        xml_to_json(xml.as_bytes()).map_err(|e| EtlError::ParseError(format!("Invalid S3 response: {}", e)))
+    }
+}
+
+/// 尚未完成的 multipart upload
+struct PendingUpload {
+    client: Arc<S3Client>,
+    bucket: String,
+    key: String,
+    upload_id: String,
+}
+
+impl PendingUpload {
+    async fn abort(self) {
+        match self.client.send("DELETE", &self.key, &[("uploadId", &self.upload_id)], None, None).await {
+            Ok(_) => warn!("Aborted multipart upload {} for s3://{}/{}", self.upload_id, self.bucket, self.key),
+            Err(e) => warn!(
+                "Failed to abort multipart upload {} for s3://{}/{}: {}",
+                self.upload_id, self.bucket, self.key, e
+            ),
+        }
+    }
+}
+
+/// 上傳的 future 在完成前被丟棄（例如逾時或 Ctrl-C）時，於背景中止 upload
+struct AbortGuard {
+    upload: Option<PendingUpload>,
+}
+
+impl Drop for AbortGuard {
+    fn drop(&mut self) {
+        let Some(upload) = self.upload.take() else {
+            return;
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(upload.abort());
+            }
+            Err(_) => warn!(
+                "Multipart upload {} for s3://{}/{} was cancelled and could not be aborted",
+                upload.upload_id, upload.bucket, upload.key
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::{AwsCredentials, RetryConfig};
+    use crate::test_support::{sample_records, RecordedRequest, TestResponse, TestServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn writer(endpoint: &str, initial_delay_ms: u64) -> S3Writer {
+        let destination = OutputDestination::S3 {
+            bucket: "exports".to_string(),
+            key: "out/data.csv".to_string(),
+            region: "us-east-1".to_string(),
+            credentials: Some(AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".to_string(),
+                secret_access_key: "secret".to_string(),
+                session_token: None,
+            }),
+            endpoint: Some(endpoint.to_string()),
+            path_style: None,
+            content_type: None,
+            metadata: None,
+            server_side_encryption: None,
+            part_size_mb: None,
+            retry: Some(RetryConfig {
+                max_attempts: 3,
+                initial_delay_ms,
+                max_delay_ms: initial_delay_ms,
+                backoff_multiplier: 1.0,
+                jitter: Some(false),
+                retry_on_status: None,
+            }),
+        };
+        let format = OutputFormat::Csv { delimiter: None, quote_char: None, headers: None };
+        let mut writer = S3Writer::from_config(&destination, &format, &HashMap::new()).unwrap();
+        // 讓幾筆記錄就分成多段
+        writer.part_size = 16;
+        writer
+    }
+
+    /// 建立、完成與中止 upload 的回應；分段請求交給 `part`
+    fn respond(request: &RecordedRequest, part: impl Fn(&str) -> TestResponse) -> TestResponse {
+        let query = request.query();
+        match request.method.as_str() {
+            "POST" if query == "uploads=" => TestResponse::new(
+                200,
+                "<InitiateMultipartUploadResult><UploadId>upload-1</UploadId></InitiateMultipartUploadResult>",
+            ),
+            "PUT" => {
+                let number = query.split('&').find_map(|p| p.strip_prefix("partNumber=")).unwrap_or_default();
+                part(number)
+            }
+            "POST" => TestResponse::new(200, "<CompleteMultipartUploadResult/>"),
+            "DELETE" => TestResponse::new(204, ""),
+            _ => TestResponse::new(400, ""),
+        }
+    }
+
+    fn methods(server: &TestServer) -> Vec<String> {
+        server.requests().iter().map(|r| r.method.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn failed_part_is_retried() {
+        let failures = Arc::new(AtomicUsize::new(0));
+        let counter = failures.clone();
+        let server = TestServer::start(move |request| {
+            respond(request, |number| {
+                if number == "2" && counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    TestResponse::new(500, "<Error><Code>InternalError</Code><Message>retry</Message></Error>")
+                } else {
+                    TestResponse::new(200, "").header("ETag", &format!("\"etag-{}\"", number))
+                }
+            })
+        })
+        .await;
+
+        let summary = writer(&server.url, 1).write_records(&sample_records()).await.unwrap();
+
+        assert_eq!(summary.parts, 3);
+        assert_eq!(methods(&server), ["POST", "PUT", "PUT", "PUT", "PUT", "POST"]);
+        let complete = server.requests().last().unwrap().body_text();
+        assert!(complete.contains("<PartNumber>2</PartNumber><ETag>&quot;etag-2&quot;</ETag>"));
+    }
+
+    #[tokio::test]
+    async fn failed_part_aborts_with_s3_error() {
+        let server = TestServer::start(|request| {
+            respond(request, |_| {
+                TestResponse::new(
+                    403,
+                    "<Error><Code>AccessDenied</Code><Message>Access Denied</Message></Error>",
+                )
+            })
+        })
+        .await;
+
+        let error = writer(&server.url, 1).write_records(&sample_records()).await.unwrap_err();
+
+        let message = error.to_string();
+        assert!(message.contains("AccessDenied: Access Denied"), "{}", message);
+        assert_eq!(methods(&server), ["POST", "PUT", "DELETE"]);
+        assert_eq!(server.requests()[2].query(), "uploadId=upload-1");
+    }
+
+    #[tokio::test]
+    async fn cancelled_upload_is_aborted() {
+        let server = TestServer::start(|request| {
+            respond(request, |_| TestResponse::new(503, "<Error><Code>SlowDown</Code></Error>"))
+        })
+        .await;
+        let writer = writer(&server.url, 60_000);
+
+        // 第一段回應 503 後進入長時間的重試等待，此時丟棄 future
+        let result = tokio::time::timeout(Duration::from_millis(500), writer.write_records(&sample_records())).await;
+        assert!(result.is_err());
+
+        for _ in 0..50 {
+            if methods(&server).contains(&"DELETE".to_string()) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(methods(&server), ["POST", "PUT", "DELETE"]);
+    }
+}
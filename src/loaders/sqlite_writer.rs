@@ -0,0 +1,313 @@
+use crate::config::settings::{OutputDestination, OutputFormat, OutputOptions, WriteMode};
+use crate::extractors::sqlite::{database_path, to_sql_value};
+use crate::loaders::csv_writer::CsvWriter;
+use crate::models::data_types::DataRecord;
+use crate::utils::error::{EtlError, Result};
+use rusqlite::{Connection, Transaction};
+use std::collections::HashSet;
+use tracing::info;
+
+const DEFAULT_BATCH_SIZE: usize = 500;
+/// SQLite 單一語句可綁定的參數上限
+const MAX_VARIABLES: usize = 32766;
+
+/// 依記錄推斷欄位型別建立資料表，在單一交易內以多列 INSERT 分批寫入
+#[derive(Clone)]
+pub struct SqliteWriter {
+    path: String,
+    table: String,
+    mode: WriteMode,
+    key_fields: Vec<String>,
+    batch_size: usize,
+}
+
+impl SqliteWriter {
+    /// 由 `OutputDestination::Database` 與 `OutputFormat::Database` 建立
+    pub fn from_config(
+        destination: &OutputDestination,
+        format: &OutputFormat,
+        options: Option<&OutputOptions>,
+    ) -> Result<Self> {
+        let (OutputDestination::Database { connection_string, .. }, OutputFormat::Database { table_name, mode, key_fields }) =
+            (destination, format)
+        else {
+            return Err(EtlError::ConfigError(
+                "SqliteWriter requires a database output destination and format".to_string(),
+            ));
+        };
+
+        let key_fields = key_fields.clone().unwrap_or_default();
+        if matches!(mode, WriteMode::Upsert) && key_fields.is_empty() {
+            return Err(EtlError::ConfigError("upsert mode requires key_fields".to_string()));
+        }
+
+        Ok(Self {
+            path: database_path(connection_string).to_string(),
+            table: table_name.clone(),
+            mode: mode.clone(),
+            key_fields,
+            batch_size: options
+                .and_then(|o| o.batch_size)
+                .unwrap_or(DEFAULT_BATCH_SIZE)
+                .max(1),
+        })
+    }
+
+    /// 回傳寫入的列數；任何一批失敗時整個交易回滾
+    pub async fn write_records(&self, records: &[DataRecord]) -> Result<usize> {
+        let writer = self.clone();
+        let records = records.to_vec();
+        let written = tokio::task::spawn_blocking(move || writer.write_blocking(&records))
+            .await
+            .map_err(|e| EtlError::DatabaseError(format!("SQLite writer task failed: {}", e)))??;
+        info!("Wrote {} records to SQLite table {} in {}", written, self.table, self.path);
+        Ok(written)
+    }
+
+    fn write_blocking(&self, records: &[DataRecord]) -> Result<usize> {
+        let mut connection = Connection::open(&self.path)
+            .map_err(|e| EtlError::DatabaseError(format!("Failed to open SQLite database {}: {}", self.path, e)))?;
+        let transaction = connection.transaction()?;
+
+        let columns = CsvWriter::headers_from_records(records);
+        if let Some(missing) = self.key_fields.iter().find(|k| !records.is_empty() && !columns.contains(k)) {
+            return Err(EtlError::ValidationError(format!("Key field '{}' not found in records", missing)));
+        }
+
+        let overwrite = matches!(self.mode, WriteMode::Overwrite);
+        if records.is_empty() {
+            // 無法推斷欄位，保留資料表結構只清空內容
+            if overwrite && Self::table_exists(&transaction, &self.table)? {
+                transaction.execute(&format!("DELETE FROM {}", quote(&self.table)), [])?;
+            }
+            transaction.commit()?;
+            return Ok(0);
+        }
+        if overwrite {
+            transaction.execute(&format!("DROP TABLE IF EXISTS {}", quote(&self.table)), [])?;
+        }
+        self.ensure_table(&transaction, records, &columns)?;
+
+        let rows_per_statement = self.batch_size.min(MAX_VARIABLES / columns.len()).max(1);
+        for batch in records.chunks(rows_per_statement) {
+            let mut statement = transaction.prepare_cached(&self.insert_sql(&columns, batch.len()))?;
+            let values: Vec<rusqlite::types::Value> = batch
+                .iter()
+                .flat_map(|record| {
+                    columns.iter().map(|column| {
+                        record.fields.get(column).map(to_sql_value).unwrap_or(rusqlite::types::Value::Null)
+                    })
+                })
+                .collect();
+            statement.execute(rusqlite::params_from_iter(values))?;
+        }
+
+        transaction.commit()?;
+        Ok(records.len())
+    }
+
+    /// 資料表不存在時建立，已存在時補上新欄位；upsert 時依 `key_fields` 建立 `ON CONFLICT` 所需的唯一索引
+    fn ensure_table(&self, transaction: &Transaction, records: &[DataRecord], columns: &[String]) -> Result<()> {
+        let existing: HashSet<String> = transaction
+            .prepare(&format!("PRAGMA table_info({})", quote(&self.table)))?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        if existing.is_empty() {
+            let definitions: Vec<String> = columns
+                .iter()
+                .map(|column| format!("{} {}", quote(column), column_type(records, column)))
+                .collect();
+            transaction.execute(
+                &format!("CREATE TABLE {} ({})", quote(&self.table), definitions.join(", ")),
+                [],
+            )?;
+        } else {
+            for column in columns.iter().filter(|c| !existing.contains(*c)) {
+                transaction.execute(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN {} {}",
+                        quote(&self.table),
+                        quote(column),
+                        column_type(records, column)
+                    ),
+                    [],
+                )?;
+            }
+        }
+
+        if matches!(self.mode, WriteMode::Upsert) {
+            transaction.execute(
+                &format!(
+                    "CREATE UNIQUE INDEX IF NOT EXISTS {} ON {} ({})",
+                    quote(&format!("{}_key", self.table)),
+                    quote(&self.table),
+                    self.key_fields.iter().map(|k| quote(k)).collect::<Vec<_>>().join(", ")
+                ),
+                [],
+            )?;
+        }
+        Ok(())
+    }
+
+    fn table_exists(transaction: &Transaction, table: &str) -> Result<bool> {
+        let count: i64 = transaction.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [table],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    fn insert_sql(&self, columns: &[String], rows: usize) -> String {
+        let placeholders = format!("({})", vec!["?"; columns.len()].join(", "));
+        let mut sql = format!(
+            "INSERT INTO {} ({}) VALUES {}",
+            quote(&self.table),
+            columns.iter().map(|c| quote(c)).collect::<Vec<_>>().join(", "),
+            vec![placeholders; rows].join(", ")
+        );
+        if matches!(self.mode, WriteMode::Upsert) {
+            let updates: Vec<String> = columns
+                .iter()
+                .filter(|c| !self.key_fields.contains(c))
+                .map(|c| format!("{0} = excluded.{0}", quote(c)))
+                .collect();
+            let keys = self.key_fields.iter().map(|k| quote(k)).collect::<Vec<_>>().join(", ");
+            if updates.is_empty() {
+                sql.push_str(&format!(" ON CONFLICT ({}) DO NOTHING", keys));
+            } else {
+                sql.push_str(&format!(" ON CONFLICT ({}) DO UPDATE SET {}", keys, updates.join(", ")));
+            }
+        }
+        sql
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// 全為整數或布林時為 INTEGER，含小數時為 REAL，其他為 TEXT
+fn column_type(records: &[DataRecord], column: &str) -> &'static str {
+    let mut column_type = None;
+    for value in records.iter().filter_map(|record| record.fields.get(column)) {
+        let value_type = match value {
+            serde_json::Value::Null => continue,
+            serde_json::Value::Bool(_) => "INTEGER",
+            serde_json::Value::Number(n) if n.is_i64() => "INTEGER",
+            serde_json::Value::Number(_) => "REAL",
+            _ => return "TEXT",
+        };
+        column_type = match (column_type, value_type) {
+            (None, value_type) => Some(value_type),
+            (Some("INTEGER"), "REAL") => Some("REAL"),
+            (current, _) => current,
+        };
+    }
+    column_type.unwrap_or("TEXT")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractors::sqlite::{QueryParams, SqliteReader};
+    use crate::test_support::record;
+    use serde_json::json;
+
+    fn writer(path: &std::path::Path, mode: WriteMode, key_fields: Option<Vec<&str>>, batch_size: usize) -> SqliteWriter {
+        let destination = OutputDestination::Database {
+            connection_string: format!("sqlite://{}", path.display()),
+            driver: crate::config::settings::DatabaseDriver::Sqlite,
+            namespace: None,
+            database: None,
+        };
+        let format = OutputFormat::Database {
+            table_name: "items".to_string(),
+            mode,
+            key_fields: key_fields.map(|keys| keys.into_iter().map(str::to_string).collect()),
+        };
+        let options = OutputOptions {
+            batch_size: Some(batch_size),
+            max_file_size: None,
+            split_by_field: None,
+            filename_template: None,
+        };
+        SqliteWriter::from_config(&destination, &format, Some(&options)).unwrap()
+    }
+
+    async fn rows(path: &std::path::Path) -> Vec<serde_json::Value> {
+        SqliteReader::new(&path.display().to_string())
+            .query("SELECT * FROM items ORDER BY sku", QueryParams::None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| serde_json::to_value(record.fields).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn column_type_inference() {
+        let records = vec![
+            record(json!({"int": 1, "mixed": 1, "flag": true, "text": 1, "empty": null})),
+            record(json!({"int": 2, "mixed": 1.5, "flag": false, "text": "a"})),
+        ];
+        assert_eq!(column_type(&records, "int"), "INTEGER");
+        assert_eq!(column_type(&records, "mixed"), "REAL");
+        assert_eq!(column_type(&records, "flag"), "INTEGER");
+        assert_eq!(column_type(&records, "text"), "TEXT");
+        assert_eq!(column_type(&records, "empty"), "TEXT");
+        assert_eq!(column_type(&records, "missing"), "TEXT");
+    }
+
+    #[tokio::test]
+    async fn overwrite_append_and_upsert() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("etl.db");
+
+        let first = vec![record(json!({"sku": "a", "qty": 1})), record(json!({"sku": "b", "qty": 2}))];
+        writer(&path, WriteMode::Overwrite, None, 500).write_records(&first).await.unwrap();
+        writer(&path, WriteMode::Overwrite, None, 500).write_records(&first).await.unwrap();
+        assert_eq!(rows(&path).await.len(), 2);
+
+        // append 不建立唯一索引，相同 key 的列可以重複
+        let appended = vec![record(json!({"sku": "a", "qty": 5, "note": "new column"}))];
+        writer(&path, WriteMode::Append, Some(vec!["sku"]), 500).write_records(&appended).await.unwrap();
+        assert_eq!(
+            rows(&path).await,
+            [
+                json!({"sku": "a", "qty": 1, "note": null}),
+                json!({"sku": "a", "qty": 5, "note": "new column"}),
+                json!({"sku": "b", "qty": 2, "note": null}),
+            ]
+        );
+
+        writer(&path, WriteMode::Overwrite, None, 500).write_records(&first).await.unwrap();
+        let updates = vec![record(json!({"sku": "a", "qty": 10})), record(json!({"sku": "c", "qty": 3}))];
+        writer(&path, WriteMode::Upsert, Some(vec!["sku"]), 1).write_records(&updates).await.unwrap();
+        assert_eq!(
+            rows(&path).await,
+            [json!({"sku": "a", "qty": 10}), json!({"sku": "b", "qty": 2}), json!({"sku": "c", "qty": 3})]
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_batch_rolls_back_all_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("etl.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute("CREATE TABLE items (sku TEXT NOT NULL, qty INTEGER)", [])
+            .unwrap();
+
+        let records = vec![
+            record(json!({"sku": "a", "qty": 1})),
+            record(json!({"sku": "b", "qty": 2})),
+            record(json!({"sku": null, "qty": 3})),
+        ];
+        let result = writer(&path, WriteMode::Append, None, 1).write_records(&records).await;
+
+        assert!(result.is_err());
+        assert!(rows(&path).await.is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+use crate::config::settings::{OutputDestination, OutputFormat, OutputOptions, WriteMode};
+use crate::extractors::surreal::{connect, SurrealSession, IDENTIFIER};
+use crate::models::data_types::DataRecord;
+use crate::utils::error::{EtlError, Result};
+use tracing::{debug, info};
+
+const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// 將記錄寫入 SurrealDB 資料表，所有批次在同一個交易內完成
+pub struct SurrealWriter {
+    db: SurrealSession,
+    table: String,
+    mode: WriteMode,
+    key_fields: Vec<String>,
+    batch_size: usize,
+}
+
+impl SurrealWriter {
+    /// 由 `OutputDestination::Database` 與 `OutputFormat::Database` 建立
+    pub async fn from_config(
+        destination: &OutputDestination,
+        format: &OutputFormat,
+        options: Option<&OutputOptions>,
+    ) -> Result<Self> {
+        let (
+            OutputDestination::Database { connection_string, namespace, database, .. },
+            OutputFormat::Database { table_name, mode, key_fields },
+        ) = (destination, format)
+        else {
+            return Err(EtlError::ConfigError(
+                "SurrealWriter requires a database output destination and format".to_string(),
+            ));
+        };
+
+        if !IDENTIFIER.is_match(table_name) {
+            return Err(EtlError::ConfigError(format!(
+                "Invalid SurrealDB table name '{}': use letters, digits and underscores",
+                table_name
+            )));
+        }
+        let key_fields = key_fields.clone().unwrap_or_default();
+        if matches!(mode, WriteMode::Upsert) && key_fields.is_empty() {
+            return Err(EtlError::ConfigError("upsert mode requires key_fields".to_string()));
+        }
+
+        Ok(Self {
+            db: connect(connection_string, namespace.as_deref(), database.as_deref()).await?,
+            table: table_name.clone(),
+            mode: mode.clone(),
+            key_fields,
+            batch_size: options
+                .and_then(|o| o.batch_size)
+                .unwrap_or(DEFAULT_BATCH_SIZE)
+                .max(1),
+        })
+    }
+
+    /// 回傳寫入的記錄數；`overwrite` 的清空與所有批次在同一個交易內，任何一批失敗時資料表維持原狀
+    pub async fn write_records(&self, records: &[DataRecord]) -> Result<usize> {
+        let rows = records.iter().map(|record| self.row(record)).collect::<Result<Vec<_>>>()?;
+        let batches: Vec<&[serde_json::Value]> = rows.chunks(self.batch_size).collect();
+
+        let mut statements = vec!["BEGIN TRANSACTION;".to_string()];
+        if matches!(self.mode, WriteMode::Overwrite) {
+            statements.push(format!("DELETE {};", self.table));
+        }
+        for index in 0..batches.len() {
+            statements.push(match self.mode {
+                WriteMode::Upsert => format!(
+                    "FOR $row IN $rows_{} {{ UPSERT type::thing('{}', $row.id) MERGE $row.content }};",
+                    index, self.table
+                ),
+                WriteMode::Overwrite | WriteMode::Append => {
+                    format!("INSERT INTO {} $rows_{};", self.table, index)
+                }
+            });
+        }
+        statements.push("COMMIT TRANSACTION;".to_string());
+
+        let mut query = self.db.query(statements.join("\n"));
+        for (index, batch) in batches.iter().enumerate() {
+            query = query.bind((format!("rows_{}", index), batch.to_vec()));
+        }
+        query.await?.check()?;
+        debug!("Wrote {} batches to SurrealDB table {}", batches.len(), self.table);
+
+        info!("Wrote {} records to SurrealDB table {}", rows.len(), self.table);
+        Ok(rows.len())
+    }
+
+    /// 設定 `key_fields` 時以其值作為 record ID；`upsert` 的 ID 與內容分開，MERGE 的內容不能含 `id`
+    fn row(&self, record: &DataRecord) -> Result<serde_json::Value> {
+        let mut row: serde_json::Map<String, serde_json::Value> =
+            record.fields.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        if self.key_fields.is_empty() {
+            return Ok(serde_json::Value::Object(row));
+        }
+
+        let mut key = Vec::with_capacity(self.key_fields.len());
+        for field in &self.key_fields {
+            match record.fields.get(field) {
+                Some(value) if !value.is_null() => key.push(value.clone()),
+                _ => {
+                    return Err(EtlError::ValidationError(format!(
+                        "Key field '{}' is missing in record",
+                        field
+                    )))
+                }
+            }
+        }
+        // `id` 即為 record ID，除非唯一鍵就是 `id` 本身，否則原本的值會被覆蓋
+        if record.fields.contains_key("id") && self.key_fields != ["id"] {
+            return Err(EtlError::ValidationError(
+                "Record already has an 'id' field, which would be replaced by the key_fields record ID; \
+                 rename it in a transformation first"
+                    .to_string(),
+            ));
+        }
+        let id = match key.len() {
+            1 => key.remove(0),
+            _ => serde_json::Value::Array(key),
+        };
+        if matches!(self.mode, WriteMode::Upsert) {
+            row.remove("id");
+            return Ok(serde_json::json!({ "id": id, "content": row }));
+        }
+        row.insert("id".to_string(), id);
+        Ok(serde_json::Value::Object(row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractors::surreal::SurrealReader;
+    use crate::test_support::records;
+
+    /// 連線依連線字串快取在程序內，而每個測試有自己的 runtime，因此各測試使用獨立的資料庫檔案
+    fn database() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let connection = format!("surrealkv://{}", dir.path().join("db").display());
+        (dir, connection)
+    }
+
+    async fn writer(
+        connection: &str,
+        namespace: &str,
+        mode: WriteMode,
+        key_fields: Option<Vec<&str>>,
+    ) -> SurrealWriter {
+        let destination = OutputDestination::Database {
+            connection_string: connection.to_string(),
+            driver: crate::config::settings::DatabaseDriver::Surreal,
+            namespace: Some(namespace.to_string()),
+            database: None,
+        };
+        let format = OutputFormat::Database {
+            table_name: "items".to_string(),
+            mode,
+            key_fields: key_fields.map(|k| k.into_iter().map(str::to_string).collect()),
+        };
+        let options = OutputOptions {
+            batch_size: Some(1),
+            max_file_size: None,
+            split_by_field: None,
+            filename_template: None,
+        };
+        SurrealWriter::from_config(&destination, &format, Some(&options)).await.unwrap()
+    }
+
+    async fn names(connection: &str, namespace: &str) -> Vec<serde_json::Value> {
+        let reader = SurrealReader::connect(connection, Some(namespace), None).await.unwrap();
+        reader.query("SELECT VALUE name FROM items ORDER BY name", None).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_overwrite_keeps_existing_rows() {
+        let (_dir, db) = database();
+        let key = Some(vec!["code"]);
+        writer(&db, "etl", WriteMode::Append, key.clone())
+            .await
+            .write_records(&records(serde_json::json!([{ "code": "a", "name": "old" }])))
+            .await
+            .unwrap();
+
+        // 第三批的 record ID 重複，INSERT 失敗
+        let failing = records(serde_json::json!([
+            { "code": "b", "name": "new b" },
+            { "code": "c", "name": "new c" },
+            { "code": "c", "name": "duplicate" },
+        ]));
+        let overwrite = writer(&db, "etl", WriteMode::Overwrite, key).await;
+        assert!(overwrite.write_records(&failing).await.is_err());
+        assert_eq!(names(&db, "etl").await, vec![serde_json::json!("old")]);
+
+        overwrite.write_records(&failing[..2]).await.unwrap();
+        assert_eq!(names(&db, "etl").await, vec![serde_json::json!("new b"), serde_json::json!("new c")]);
+    }
+
+    #[tokio::test]
+    async fn existing_id_field_is_rejected() {
+        let (_dir, db) = database();
+        let rows = records(serde_json::json!([{ "id": 7, "code": "a", "name": "x" }]));
+        for mode in [WriteMode::Append, WriteMode::Upsert] {
+            let writer = writer(&db, "etl", mode, Some(vec!["code"])).await;
+            assert!(matches!(writer.write_records(&rows).await, Err(EtlError::ValidationError(_))));
+        }
+
+        let by_id = writer(&db, "etl", WriteMode::Upsert, Some(vec!["id"])).await;
+        assert_eq!(by_id.write_records(&rows).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn sessions_on_one_connection_keep_their_namespace() {
+        let (_dir, db) = database();
+        let first = writer(&db, "scope_a", WriteMode::Append, None).await;
+        let second = writer(&db, "scope_b", WriteMode::Append, None).await;
+
+        first.write_records(&records(serde_json::json!([{ "name": "a1" }]))).await.unwrap();
+        second.write_records(&records(serde_json::json!([{ "name": "b1" }]))).await.unwrap();
+        first.write_records(&records(serde_json::json!([{ "name": "a2" }]))).await.unwrap();
+
+        assert_eq!(names(&db, "scope_a").await, vec![serde_json::json!("a1"), serde_json::json!("a2")]);
+        assert_eq!(names(&db, "scope_b").await, vec![serde_json::json!("b1")]);
+    }
+}
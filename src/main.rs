@@ -1,43 +1,3 @@
-use general_etl::models::data_types::{MappingRule, TransformationType};
-use general_etl::pipeline::{DataSource, EtlPipeline, OutputConfig, OutputFormat, PipelineConfig};
-
-#[tokio::main]
-async fn main_2() -> anyhow::Result<()> {
-    // 初始化日誌
-    tracing_subscriber::fmt::init();
-
-    // 建立 ETL 管線
-    let pipeline = EtlPipeline::new("https://sampleapis.com/switch/games".to_string());
-
-    // 設定管線配置
-    let config = PipelineConfig {
-        source: DataSource::Api("switch/games".to_string()),
-        mapping_file: "config/mappings/product_mapping.json".to_string(),
-        rules: vec![
-            MappingRule {
-                source_field: "name".to_string(),
-                target_field: "game_name".to_string(),
-                transformation: Some(TransformationType::Uppercase),
-            },
-            MappingRule {
-                source_field: "id".to_string(),
-                target_field: "source_id".to_string(),
-                transformation: Some(TransformationType::Lookup("category".to_string())),
-            },
-        ],
-        output: OutputConfig {
-            format: OutputFormat::Csv,
-            path: "output/processed_data.zip".to_string(),
-            compress: true,
-        },
-    };
-
-    // 執行管線
-    // pipeline.run(config).await?;
-    // main_surreal_test()?;
-    Ok(())
-}
-
 use serde::{Deserialize, Serialize};
 use surrealdb::RecordId;
 use surrealdb::Surreal;
@@ -73,7 +33,7 @@ struct Record {
 }
 
 #[tokio::main]
-async fn main() -> surrealdb::Result<()> {
+async fn main() -> anyhow::Result<()> {
     // Create database connection in memory
     let db = Surreal::new::<Mem>(()).await?;
 
@@ -12,13 +12,16 @@ pub struct ProcessedData {
     pub metadata: Metadata,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Metadata {
     pub source: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub record_count: usize,
 }
 
+pub use crate::config::settings::TransformationType;
+use crate::config::settings::TransformationConfig;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct MappingRule {
     pub source_field: String,
@@ -26,11 +29,14 @@ pub struct MappingRule {
     pub transformation: Option<TransformationType>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum TransformationType {
-    Uppercase,
-    Lowercase,
-    Calculate(String),
-    Lookup(String),
+impl From<MappingRule> for TransformationConfig {
+    fn from(rule: MappingRule) -> Self {
+        TransformationConfig {
+            name: rule.target_field.clone(),
+            source_field: rule.source_field,
+            target_field: Some(rule.target_field),
+            transformation: rule.transformation.unwrap_or(TransformationType::Copy),
+            condition: None,
+        }
+    }
 }
@@ -1,26 +1,72 @@
+use crate::config::settings::{
+    self, DataSourceConfig, DatabaseDriver, EtlConfig, FileFormat, OutputDestination, ResponseFormat, TransformationConfig,
+};
 use crate::extractors::api_client::ApiClient;
-use crate::transformers::processor::DataProcessor;
-use crate::loaders::{csv_writer::CsvWriter, archiver::Archiver};
+use crate::extractors::file_reader::FileReader;
+use crate::extractors::graphql::GraphQlExtractor;
+use crate::extractors::rate_limiter::RateLimiters;
+use crate::extractors::s3::S3Client;
+use crate::extractors::sqlite::{QueryParams, SqliteReader};
+use crate::extractors::surreal::SurrealReader;
+use crate::transformers::{enricher::ApiEnricher, mapper::MappingLoader, processor::DataProcessor};
+use crate::loaders::{api_writer::ApiWriter, csv_writer::CsvWriter, archiver::Archiver, file_writer::LocalFileWriter, s3_writer::S3Writer, sqlite_writer::SqliteWriter, surreal_writer::SurrealWriter};
 use crate::models::data_types::{DataRecord, ProcessedData, MappingRule};
-use crate::utils::error::Result;
+use crate::utils::error::{EtlError, Result};
+use crate::utils::template::TemplateContext;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek};
+use std::path::{Path, PathBuf};
 use indicatif::{ProgressBar, ProgressStyle};
-use tracing::{info, error};
+use tracing::info;
 
 pub struct EtlPipeline {
     api_client: ApiClient,
+    file_reader: FileReader,
     processor: DataProcessor,
-    csv_writer: CsvWriter,
+    archiver: Archiver,
+    /// 同一主機的資料源與 enrich 請求共用速率限制
+    rate_limiters: RateLimiters,
+    /// `${VAR}` 模板變數，例如 `settings.variables` 或排程器提供的 `LAST_RUN_DATE`
+    variables: HashMap<String, String>,
 }
 
 impl EtlPipeline {
-    pub fn new(base_url: String) -> Self {
+    pub fn new(_base_url: String) -> Self {
         Self {
             api_client: ApiClient::new(),
+            file_reader: FileReader::new(),
             processor: DataProcessor::new(),
-            csv_writer: CsvWriter::new(b','),
+            archiver: Archiver::default(),
+            rate_limiters: RateLimiters::default(),
+            variables: HashMap::new(),
         }
     }
 
+    /// 以 `settings.variables` 作為模板變數建立
+    pub fn from_config(config: &EtlConfig) -> Self {
+        let variables = config.settings.as_ref().and_then(|s| s.variables.clone()).unwrap_or_default();
+        Self::new(String::new()).with_variables(variables)
+    }
+
+    /// 加入模板變數，同名時覆寫先前的值（例如排程器提供的 `LAST_RUN_DATE` 覆寫 `settings.variables`）
+    pub fn with_variables(mut self, variables: HashMap<String, String>) -> Self {
+        self.variables.extend(variables);
+        self
+    }
+
+    /// 依 JSON 配置擷取、轉換並輸出，回傳輸出的記錄數；`settings.mapping_file` 的查找表在轉換前載入
+    pub async fn run_config(&self, config: &EtlConfig) -> Result<usize> {
+        let mapping_file = config.settings.as_ref().and_then(|s| s.mapping_file.as_deref()).unwrap_or_default();
+        self.load_mappings(mapping_file).await?;
+
+        let records = self.extract_source(&config.data_source).await?;
+        let records = self.transform_records(records, &config.transformations).await?;
+        self.load_destination(&records, &config.output).await?;
+        info!("Pipeline {} finished with {} records", config.name, records.len());
+        Ok(records.len())
+    }
+
     pub async fn run(&self, config: PipelineConfig) -> Result<()> {
         let pb = ProgressBar::new(4);
         pb.set_style(
@@ -41,7 +87,7 @@ impl EtlPipeline {
 
         // 步驟 3: 轉換資料
         pb.set_message("Transforming data...");
-        let processed = self.transform_data(raw_data, &config.rules)?;
+        let processed = self.transform_data(raw_data, &config.rules).await?;
         pb.inc(1);
 
         // 步驟 4: 輸出結果
@@ -56,46 +102,176 @@ impl EtlPipeline {
     async fn extract_data(&self, source: &DataSource) -> Result<Vec<DataRecord>> {
         match source {
             DataSource::Api(endpoint) => {
-                let json = self.api_client.fetch_json(endpoint, None, None, None, None).await?;
+                let body = self.api_client.fetch_text(endpoint, None, None, None, None).await?;
+                Self::ensure_not_empty(endpoint, body.trim().is_empty())?;
+                let json = serde_json::from_str(&body).map_err(|e| {
+                    EtlError::ParseError(format!("Invalid JSON response from {}: {}", endpoint, e))
+                })?;
                 self.parse_json_to_records(json)
             }
+            // CSV 與 ZIP 匯出可能很大，先串流寫入暫存檔再解析
             DataSource::CsvApi(endpoint) => {
-                let csv_data = self.api_client.fetch_text(endpoint, None, None, None, None).await?;
-                self.parse_csv_to_records(&csv_data)
+                let download = self.api_client.download(endpoint, None, None, None, None, None).await?;
+                Self::ensure_not_empty(endpoint, download.size() == 0)?;
+                self.parse_csv_to_records(File::open(download.path())?)
             }
             DataSource::ZipApi(endpoint) => {
-                let zip_data = self.api_client.fetch_bytes(endpoint, None, None, None, None).await?;
-                self.extract_and_parse_zip(zip_data)
+                let download = self.api_client.download(endpoint, None, None, None, None, None).await?;
+                Self::ensure_not_empty(endpoint, download.size() == 0)?;
+                self.extract_and_parse_zip(File::open(download.path())?)
+            }
+            DataSource::AutoApi(endpoint) => {
+                let download = self.api_client.download(endpoint, None, None, None, None, None).await?;
+                let records = download.parse(ResponseFormat::Auto, None, None)?;
+                self.parse_json_to_records(serde_json::Value::Array(records))
             }
         }
     }
 
+    /// 依照 JSON 配置的資料源擷取資料
+    pub async fn extract_source(&self, source: &DataSourceConfig) -> Result<Vec<DataRecord>> {
+        match source {
+            DataSourceConfig::Api(api) => {
+                let api = &ApiClient::render_source(api, &TemplateContext::new(self.variables.clone()))?;
+                let rate_limiter = self.rate_limiters.for_url(&api.url, api.rate_limit.as_ref());
+                // 有速率限制、連線設定或快取的資料源使用獨立的 client，不與其他資料源共用
+                let dedicated = rate_limiter.is_some() || api.http.is_some() || api.cache.is_some();
+                let records = if dedicated {
+                    ApiClient::for_source(api)?.with_shared_rate_limit(rate_limiter).fetch_source(api).await?
+                } else {
+                    self.api_client.fetch_source(api).await?
+                };
+                self.parse_json_to_records(serde_json::Value::Array(records))
+            }
+            DataSourceConfig::Graphql(graphql) => {
+                let graphql = &GraphQlExtractor::render_source(
+                    graphql,
+                    &TemplateContext::new(self.variables.clone()),
+                )?;
+                let rate_limiter = self.rate_limiters.for_url(&graphql.url, graphql.rate_limit.as_ref());
+                let records = GraphQlExtractor::for_source(graphql)?
+                    .with_shared_rate_limit(rate_limiter)
+                    .fetch(graphql)
+                    .await?;
+                self.parse_json_to_records(serde_json::Value::Array(records))
+            }
+            DataSourceConfig::LocalFile { path, format, records_path, envelope_fields, .. } => {
+                match (format, records_path.is_some() || envelope_fields.is_some()) {
+                    (_, false) => self.file_reader.read_file(path, format.clone()).await,
+                    (FileFormat::Json, true) => {
+                        self.file_reader
+                            .read_json_selected(path, records_path.as_deref(), envelope_fields.as_ref())
+                            .await
+                    }
+                    (_, true) => Err(EtlError::ConfigError(
+                        "records_path and envelope_fields only apply to JSON files".to_string(),
+                    )),
+                }
+            }
+            DataSourceConfig::Database { connection_string, query, driver: DatabaseDriver::Sqlite, params, .. } => {
+                let params = TemplateContext::new(self.variables.clone()).render_value(
+                    params.as_ref().unwrap_or(&serde_json::Value::Null),
+                )?;
+                SqliteReader::new(connection_string).query(query, QueryParams::from_json(Some(&params))?).await
+            }
+            DataSourceConfig::Database {
+                connection_string,
+                query,
+                driver: DatabaseDriver::Surreal,
+                params,
+                namespace,
+                database,
+            } => {
+                let params = params
+                    .as_ref()
+                    .map(|p| TemplateContext::new(self.variables.clone()).render_value(p))
+                    .transpose()?;
+                let reader =
+                    SurrealReader::connect(connection_string, namespace.as_deref(), database.as_deref()).await?;
+                let rows = reader.query(query, params.as_ref()).await?;
+                self.parse_json_to_records(serde_json::Value::Array(rows))
+            }
+            DataSourceConfig::Database { driver, .. } => Err(EtlError::ConfigError(format!(
+                "Database driver {:?} is not yet supported as a source",
+                driver
+            ))),
+            DataSourceConfig::S3 { bucket, key, region, credentials, endpoint, path_style, format } => {
+                let client =
+                    S3Client::new(bucket, region, credentials.clone(), endpoint.as_deref(), *path_style)?;
+                let key = TemplateContext::new(self.variables.clone()).render(key)?;
+                let mut records = Vec::new();
+                for key in client.resolve_keys(&key).await? {
+                    let download = client.get_object(&key).await?;
+                    let path = download.path().to_string_lossy();
+                    let object_records = match format.clone().or_else(|| S3Client::format_for_key(&key)) {
+                        Some(format) => self.file_reader.read_file(&path, format).await?,
+                        None => self.parse_json_to_records(serde_json::Value::Array(
+                            download.parse(ResponseFormat::Auto, None, None)?,
+                        ))?,
+                    };
+                    info!("Read {} records from s3://{}/{}", object_records.len(), bucket, key);
+                    records.extend(object_records);
+                }
+                Ok(records)
+            }
+        }
+    }
+
+    fn ensure_not_empty(endpoint: &str, is_empty: bool) -> Result<()> {
+        if is_empty {
+            return Err(EtlError::ParseError(format!("Empty response from {}", endpoint)));
+        }
+        Ok(())
+    }
+
     fn parse_json_to_records(&self, json: serde_json::Value) -> Result<Vec<DataRecord>> {
-        // 實作 JSON 解析邏輯
-        Ok(vec![])
+        self.file_reader.parse_json(json)
     }
 
-    fn parse_csv_to_records(&self, csv_data: &str) -> Result<Vec<DataRecord>> {
-        // 實作 CSV 解析邏輯
-        Ok(vec![])
+    fn parse_csv_to_records(&self, reader: impl Read) -> Result<Vec<DataRecord>> {
+        self.file_reader.parse_csv(BufReader::new(reader), ',', true)
     }
 
-    fn extract_and_parse_zip(&self, zip_data: Vec<u8>) -> Result<Vec<DataRecord>> {
-        // 實作 ZIP 解壓縮和解析邏輯
-        Ok(vec![])
+    fn extract_and_parse_zip(&self, reader: impl Read + Seek) -> Result<Vec<DataRecord>> {
+        let records = self.file_reader.parse_zip(BufReader::new(reader), &[], None)?;
+        if records.is_empty() {
+            return Err(EtlError::ParseError(
+                "Zip archive contains no CSV or JSON records".to_string(),
+            ));
+        }
+        Ok(records)
     }
 
     async fn load_mappings(&self, mapping_file: &str) -> Result<()> {
-        // 載入 mapping 檔案
+        if mapping_file.is_empty() {
+            return Ok(());
+        }
+
+        for (name, table) in MappingLoader::load_file(mapping_file)? {
+            info!("Loaded lookup table '{}' with {} entries", name, table.len());
+            self.processor.load_mapping(name, table);
+        }
         Ok(())
     }
 
-    fn transform_data(
+    /// 先執行需要呼叫 API 的 `enrich` 轉換，再交給 `DataProcessor` 套用其餘轉換
+    pub async fn transform_records(
+        &self,
+        records: Vec<DataRecord>,
+        transformations: &[TransformationConfig],
+    ) -> Result<Vec<DataRecord>> {
+        let records = ApiEnricher::enrich_all(records, transformations, &self.rate_limiters).await?;
+        self.processor.process_transformations(records, transformations)
+    }
+
+    async fn transform_data(
         &self,
         records: Vec<DataRecord>,
         rules: &[MappingRule],
     ) -> Result<ProcessedData> {
-        let processed_records = self.processor.process_records(records, rules)?;
+        let transformations: Vec<TransformationConfig> =
+            rules.iter().cloned().map(TransformationConfig::from).collect();
+        let processed_records = self.transform_records(records, &transformations).await?;
 
         Ok(ProcessedData {
             records: processed_records.clone(),
@@ -108,9 +284,125 @@ impl EtlPipeline {
     }
 
     async fn write_output(&self, data: ProcessedData, output: &OutputConfig) -> Result<()> {
-        // 實作輸出邏輯
+        let (writer, extension) = match output.format {
+            OutputFormat::Csv => (CsvWriter::new(b','), "csv"),
+            OutputFormat::Tsv => (CsvWriter::tsv(), "tsv"),
+        };
+        let headers = CsvWriter::headers_from_records(&data.records);
+
+        let output_path = PathBuf::from(&output.path);
+        if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let (written_path, entry_name) = if output.compress {
+            let zip_path = if output_path.extension().is_some_and(|ext| ext == "zip") {
+                output_path
+            } else {
+                Self::append_extension(&output_path, "zip")
+            };
+            let entry_name = Self::entry_name(&zip_path, extension);
+
+            let mut buffer = Vec::new();
+            writer.write_to(&mut buffer, &data.records, &headers)?;
+            self.archiver.create_zip(&zip_path, vec![(entry_name.clone(), buffer)])?;
+            (zip_path, Some(entry_name))
+        } else {
+            writer.write_records(&output_path, &data.records, &headers)?;
+            (output_path, None)
+        };
+
+        self.write_manifest(&written_path, &data, &headers, entry_name)?;
+        info!("Wrote {} records to {}", data.records.len(), written_path.display());
+        Ok(())
+    }
+
+    /// 依照 JSON 配置的輸出目的地寫出記錄
+    pub async fn load_destination(
+        &self,
+        records: &[DataRecord],
+        output: &settings::OutputConfig,
+    ) -> Result<()> {
+        match &output.destination {
+            OutputDestination::LocalFile { .. } => {
+                LocalFileWriter::from_config(&output.destination, &output.format)?.write_records(records)?;
+                Ok(())
+            }
+            OutputDestination::Api { .. } => {
+                let writer = ApiWriter::from_config(&output.destination, output.options.as_ref())?;
+                let summary = writer.write_records(records).await?;
+                // 其餘批次已送出，仍回報失敗讓排程與 CI 察覺需要處理 dead-letter 檔案
+                if summary.failed_batches > 0 {
+                    return Err(EtlError::ApiError(format!(
+                        "{} of {} batches ({} records) failed, see {}",
+                        summary.failed_batches,
+                        summary.batches,
+                        summary.failed_records,
+                        writer.dead_letter_path().display()
+                    )));
+                }
+                Ok(())
+            }
+            OutputDestination::S3 { .. } => {
+                let writer = S3Writer::from_config(&output.destination, &output.format, &self.variables)?;
+                writer.write_records(records).await?;
+                Ok(())
+            }
+            OutputDestination::Database { driver: DatabaseDriver::Sqlite, .. } => {
+                let writer = SqliteWriter::from_config(&output.destination, &output.format, output.options.as_ref())?;
+                writer.write_records(records).await?;
+                Ok(())
+            }
+            OutputDestination::Database { driver: DatabaseDriver::Surreal, .. } => {
+                let writer =
+                    SurrealWriter::from_config(&output.destination, &output.format, output.options.as_ref()).await?;
+                writer.write_records(records).await?;
+                Ok(())
+            }
+            OutputDestination::Database { driver, .. } => Err(EtlError::ConfigError(format!(
+                "Database driver {:?} is not yet supported as an output",
+                driver
+            ))),
+        }
+    }
+
+    /// 在輸出檔旁寫入 `<output>.manifest.json`，記錄來源與筆數等資訊
+    fn write_manifest(
+        &self,
+        output_path: &Path,
+        data: &ProcessedData,
+        headers: &[String],
+        entry_name: Option<String>,
+    ) -> Result<()> {
+        let manifest = serde_json::json!({
+            "output": output_path.file_name().map(|n| n.to_string_lossy().to_string()),
+            "entry": entry_name,
+            "headers": headers,
+            "metadata": data.metadata,
+        });
+        let manifest_path = Self::append_extension(output_path, "manifest.json");
+        std::fs::write(manifest_path, serde_json::to_string_pretty(&manifest)?)?;
         Ok(())
     }
+
+    fn append_extension(path: &Path, extension: &str) -> PathBuf {
+        let mut path = path.as_os_str().to_owned();
+        path.push(".");
+        path.push(extension);
+        PathBuf::from(path)
+    }
+
+    fn entry_name(zip_path: &Path, extension: &str) -> String {
+        let stem = zip_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "output".to_string());
+        if Path::new(&stem).extension().is_some_and(|ext| ext == extension) {
+            stem
+        } else {
+            format!("{}.{}", stem, extension)
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -126,6 +418,8 @@ pub enum DataSource {
     Api(String),
     CsvApi(String),
     ZipApi(String),
+    /// 依回應的 Content-Type、檔名與內容自動選擇解析方式
+    AutoApi(String),
 }
 
 #[derive(Debug, Clone)]
@@ -140,3 +434,248 @@ pub enum OutputFormat {
     Csv,
     Tsv,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{records, TestResponse, TestServer};
+
+    #[tokio::test]
+    async fn csv_and_zip_endpoints_are_downloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("export.zip");
+        Archiver::default()
+            .create_zip(&zip_path, vec![("items.csv".to_string(), b"sku,qty\nb-2,5\n".to_vec())])
+            .unwrap();
+        let zip = std::fs::read(&zip_path).unwrap();
+        let server = TestServer::start(move |request| match request.path() {
+            "/items.csv" => TestResponse::new(200, "sku,qty\na-1,3\n"),
+            "/export.zip" => TestResponse::new(200, zip.clone()),
+            _ => TestResponse::new(200, ""),
+        })
+        .await;
+        let pipeline = EtlPipeline::new(String::new());
+        let sku = |records: Vec<DataRecord>| records[0].fields["sku"].clone();
+
+        let csv = pipeline.extract_data(&DataSource::CsvApi(format!("{}/items.csv", server.url))).await.unwrap();
+        assert_eq!(sku(csv), serde_json::json!("a-1"));
+        let zip = pipeline.extract_data(&DataSource::ZipApi(format!("{}/export.zip", server.url))).await.unwrap();
+        assert_eq!(sku(zip), serde_json::json!("b-2"));
+        let empty = pipeline.extract_data(&DataSource::CsvApi(format!("{}/empty.csv", server.url))).await;
+        assert!(empty.unwrap_err().to_string().contains("Empty response"));
+    }
+
+    #[tokio::test]
+    async fn settings_variables_are_used_in_templates() {
+        let server = TestServer::start(|_| TestResponse::json(200, serde_json::json!([{"id": 1}]))).await;
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("items.json");
+        let config: EtlConfig = serde_json::from_value(serde_json::json!({
+            "name": "variables",
+            "data_source": {
+                "type": "api",
+                "url": "${BASE_URL}/${VERSION}/items",
+                "query_params": { "region": "${REGION}" }
+            },
+            "transformations": [
+                { "name": "id", "source_field": "id", "transformation": { "type": "copy" } }
+            ],
+            "output": {
+                "format": { "json": { "pretty_print": false } },
+                "destination": { "type": "local_file", "path": output.display().to_string() }
+            },
+            "settings": { "variables": { "BASE_URL": server.url, "VERSION": "v1", "REGION": "tw" } }
+        }))
+        .unwrap();
+
+        let pipeline = EtlPipeline::from_config(&config)
+            .with_variables(HashMap::from([("VERSION".to_string(), "v2".to_string())]));
+        assert_eq!(pipeline.run_config(&config).await.unwrap(), 1);
+
+        let requests = server.requests();
+        assert_eq!((requests[0].path(), requests[0].query()), ("/v2/items", "region=tw"));
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), r#"[{"id":1}]"#);
+    }
+
+    #[tokio::test]
+    async fn settings_mapping_file_feeds_lookup_transformations() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("products.json");
+        std::fs::write(&input, r#"[{"sku": "A1", "category_id": 1}, {"sku": "B2", "category_id": 9}]"#).unwrap();
+        std::fs::write(dir.path().join("categories.csv"), "id,label\n1,Electronics\n").unwrap();
+        let mapping_file = dir.path().join("mapping.json");
+        std::fs::write(
+            &mapping_file,
+            serde_json::json!({
+                "lookups": [{
+                    "name": "category", "format": "csv", "path": "categories.csv",
+                    "key_column": "id", "on_missing": { "default": "Uncategorized" }
+                }]
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let output = dir.path().join("out.json");
+        let config: EtlConfig = serde_json::from_value(serde_json::json!({
+            "name": "lookup",
+            "data_source": {
+                "type": "local_file",
+                "path": input.display().to_string(),
+                "format": "json"
+            },
+            "transformations": [
+                { "name": "sku", "source_field": "sku", "transformation": { "type": "copy" } },
+                {
+                    "name": "category",
+                    "source_field": "category_id",
+                    "target_field": "category",
+                    "transformation": { "type": "lookup", "table": "category" }
+                }
+            ],
+            "output": {
+                "format": { "json": { "pretty_print": false } },
+                "destination": { "type": "local_file", "path": output.display().to_string() }
+            },
+            "settings": { "mapping_file": mapping_file.display().to_string() }
+        }))
+        .unwrap();
+
+        assert_eq!(EtlPipeline::from_config(&config).run_config(&config).await.unwrap(), 2);
+
+        let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            serde_json::json!([
+                { "sku": "A1", "category": "Electronics" },
+                { "sku": "B2", "category": "Uncategorized" }
+            ])
+        );
+    }
+
+    #[test]
+    fn example_configs_only_use_supported_transformations() {
+        let examples = std::fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/config/examples")).unwrap();
+        for entry in examples {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let config = EtlConfig::from_file(path.to_str().unwrap())
+                .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            for transformation in &config.transformations {
+                assert!(
+                    !matches!(transformation.transformation, settings::TransformationType::Calculate { .. }),
+                    "{} uses an unsupported calculate transformation",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    fn processed(rows: serde_json::Value) -> ProcessedData {
+        let records = records(rows);
+        ProcessedData {
+            metadata: crate::models::data_types::Metadata {
+                source: "API".to_string(),
+                timestamp: "2024-05-01T08:00:00Z".parse().unwrap(),
+                record_count: records.len(),
+            },
+            records,
+        }
+    }
+
+    fn output(path: &Path, format: OutputFormat, compress: bool) -> OutputConfig {
+        OutputConfig { format, path: path.display().to_string(), compress }
+    }
+
+    fn read_manifest(path: &Path) -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn tsv_output_escapes_control_characters_and_writes_a_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/report.tsv");
+        let data = processed(serde_json::json!([
+            { "id": 1, "note": "tab\there", "path": "C:\\tmp" },
+            { "id": 2, "note": "two\r\nlines" }
+        ]));
+
+        EtlPipeline::new(String::new())
+            .write_output(data, &output(&path, OutputFormat::Tsv, false))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "id\tnote\tpath\n1\ttab\\there\tC:\\\\tmp\n2\ttwo\\r\\nlines\t\n"
+        );
+        assert_eq!(
+            read_manifest(&dir.path().join("nested/report.tsv.manifest.json")),
+            serde_json::json!({
+                "output": "report.tsv",
+                "entry": null,
+                "headers": ["id", "note", "path"],
+                "metadata": { "source": "API", "timestamp": "2024-05-01T08:00:00Z", "record_count": 2 }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn compressed_output_is_a_zip_named_after_the_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let pipeline = EtlPipeline::new(String::new());
+        let cases = [
+            ("report.csv", OutputFormat::Csv, "report.csv.zip", "report.csv"),
+            ("export.zip", OutputFormat::Csv, "export.zip", "export.csv"),
+            ("data", OutputFormat::Tsv, "data.zip", "data.tsv"),
+        ];
+
+        for (path, format, zip_name, entry) in cases {
+            let data = processed(serde_json::json!([{ "id": 1, "name": "a,b" }]));
+            pipeline.write_output(data, &output(&dir.path().join(path), format, true)).await.unwrap();
+
+            let zip_path = dir.path().join(zip_name);
+            let files = Archiver::extract_zip(&zip_path, None).unwrap();
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0].0, entry);
+            let manifest = read_manifest(&EtlPipeline::append_extension(&zip_path, "manifest.json"));
+            assert_eq!((manifest["output"].as_str(), manifest["entry"].as_str()), (Some(zip_name), Some(entry)));
+        }
+
+        let csv = Archiver::extract_zip(dir.path().join("report.csv.zip"), None).unwrap().remove(0).1;
+        assert_eq!(String::from_utf8(csv).unwrap(), "id,name\n1,\"a,b\"\n");
+        assert!(!dir.path().join("report.csv").exists());
+    }
+
+    #[tokio::test]
+    async fn api_output_with_failed_batches_is_an_error() {
+        let server = TestServer::start(|request| {
+            if request.body_text().contains("\"bad\"") {
+                TestResponse::new(400, "rejected")
+            } else {
+                TestResponse::new(200, "")
+            }
+        })
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+        let dead_letter = dir.path().join("dead_letter.ndjson");
+        let output: settings::OutputConfig = serde_json::from_value(serde_json::json!({
+            "format": { "json": { "pretty_print": false } },
+            "destination": {
+                "type": "api",
+                "url": format!("{}/ingest", server.url),
+                "dead_letter_path": dead_letter.display().to_string()
+            },
+            "options": { "batch_size": 1 }
+        }))
+        .unwrap();
+        let records = records(serde_json::json!([{ "name": "good" }, { "name": "bad" }, { "name": "good" }]));
+
+        let error = EtlPipeline::new(String::new()).load_destination(&records, &output).await.unwrap_err();
+
+        assert!(error.to_string().contains("1 of 3 batches (1 records) failed"), "{}", error);
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(std::fs::read_to_string(&dead_letter).unwrap().lines().count(), 1);
+    }
+}
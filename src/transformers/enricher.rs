@@ -0,0 +1,341 @@
+use crate::config::settings::{
+    EnrichConfig, EnrichErrorPolicy, TransformationConfig, TransformationType,
+};
+use crate::extractors::api_client::{ApiClient, RequestBody};
+use crate::extractors::rate_limiter::{RateLimiter, RateLimiters};
+use crate::extractors::signing::uri_encode;
+use crate::models::data_types::DataRecord;
+use crate::transformers::processor::{evaluate_condition, TEMPLATE_FIELD};
+use crate::utils::error::{EtlError, Result};
+use crate::utils::helpers::select_path;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+use tracing::{info, warn};
+
+const DEFAULT_CONCURRENCY: usize = 4;
+
+/// 相同請求在同一次執行中只送出一次，失敗結果也會被快取
+type CachedResponse = Arc<OnceCell<std::result::Result<serde_json::Value, String>>>;
+
+/// 對每筆記錄呼叫 API，將回應中選取的欄位寫入記錄
+pub struct ApiEnricher<'a> {
+    name: &'a str,
+    config: &'a EnrichConfig,
+    client: ApiClient,
+    cache: Mutex<HashMap<String, CachedResponse>>,
+}
+
+struct EnrichRequest {
+    url: String,
+    body: Option<serde_json::Value>,
+}
+
+impl<'a> ApiEnricher<'a> {
+    /// `rate_limiter` 通常來自 `RateLimiters::for_url`，與同一主機的資料源共用配額
+    pub fn new(name: &'a str, config: &'a EnrichConfig, rate_limiter: Option<Arc<RateLimiter>>) -> Self {
+        let client = ApiClient::new().with_shared_rate_limit(rate_limiter);
+
+        Self {
+            name,
+            config,
+            client,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 依序套用所有 `enrich` 轉換，其他轉換留給 `DataProcessor`
+    pub async fn enrich_all(
+        records: Vec<DataRecord>,
+        transformations: &[TransformationConfig],
+        rate_limiters: &RateLimiters,
+    ) -> Result<Vec<DataRecord>> {
+        let mut records = records;
+        for transformation in transformations {
+            if let TransformationType::Enrich(config) = &transformation.transformation {
+                let rate_limiter = rate_limiters.for_url(&config.url, config.rate_limit.as_ref());
+                records = ApiEnricher::new(&transformation.name, config, rate_limiter)
+                    .enrich(records, transformation)
+                    .await?;
+            }
+        }
+        Ok(records)
+    }
+
+    pub async fn enrich(
+        &self,
+        records: Vec<DataRecord>,
+        transformation: &TransformationConfig,
+    ) -> Result<Vec<DataRecord>> {
+        let concurrency = self.config.concurrency.unwrap_or(DEFAULT_CONCURRENCY).max(1);
+        let total = records.len();
+
+        // buffered 保留輸入順序
+        let results: Vec<Result<Option<DataRecord>>> = stream::iter(records)
+            .map(|record| async move {
+                if let Some(condition) = &transformation.condition {
+                    if !evaluate_condition(&record, condition)? {
+                        return Ok(Some(record));
+                    }
+                }
+                self.enrich_record(record).await
+            })
+            .buffered(concurrency)
+            .collect()
+            .await;
+
+        let enriched = results.into_iter().collect::<Result<Vec<_>>>()?;
+        let enriched: Vec<DataRecord> = enriched.into_iter().flatten().collect();
+        let requests = self.cache.lock().expect("enrichment cache poisoned").len();
+        info!(
+            "Enrichment '{}': {} records, {} unique requests, {} records dropped",
+            self.name,
+            total,
+            requests,
+            total - enriched.len()
+        );
+        Ok(enriched)
+    }
+
+    async fn enrich_record(&self, mut record: DataRecord) -> Result<Option<DataRecord>> {
+        let response = match self.build_request(&record) {
+            Ok(request) => self.fetch_cached(request).await,
+            Err(e) => Err(e),
+        };
+
+        match response {
+            Ok(response) => {
+                for (field, path) in &self.config.fields {
+                    let value = select_path(&response, path).cloned().unwrap_or(serde_json::Value::Null);
+                    record.fields.insert(field.clone(), value);
+                }
+                Ok(Some(record))
+            }
+            Err(e) => match self.config.on_error {
+                EnrichErrorPolicy::Fail => Err(e),
+                EnrichErrorPolicy::Skip => {
+                    warn!("Enrichment '{}' failed, dropping record: {}", self.name, e);
+                    Ok(None)
+                }
+                EnrichErrorPolicy::Null => {
+                    warn!("Enrichment '{}' failed, writing nulls: {}", self.name, e);
+                    for field in self.config.fields.keys() {
+                        record.fields.insert(field.clone(), serde_json::Value::Null);
+                    }
+                    Ok(Some(record))
+                }
+                EnrichErrorPolicy::Keep => {
+                    warn!("Enrichment '{}' failed, keeping record as is: {}", self.name, e);
+                    Ok(Some(record))
+                }
+            },
+        }
+    }
+
+    async fn fetch_cached(&self, request: EnrichRequest) -> Result<serde_json::Value> {
+        let key = format!(
+            "{} {} {}",
+            self.config.method.as_deref().unwrap_or("GET"),
+            request.url,
+            request.body.as_ref().map(|b| b.to_string()).unwrap_or_default()
+        );
+        let cell = self
+            .cache
+            .lock()
+            .expect("enrichment cache poisoned")
+            .entry(key)
+            .or_default()
+            .clone();
+
+        let result = cell
+            .get_or_init(|| async {
+                self.send(&request).await.map_err(|e| e.to_string())
+            })
+            .await;
+
+        result.clone().map_err(EtlError::ApiError)
+    }
+
+    async fn send(&self, request: &EnrichRequest) -> Result<serde_json::Value> {
+        let response = self
+            .client
+            .fetch_with_body(
+                &request.url,
+                self.config.method.clone(),
+                self.config.headers.clone(),
+                self.config.auth.clone(),
+                self.config.retry.clone(),
+                request.body.clone().map(RequestBody::Json),
+            )
+            .await?;
+        let text = response.text().await?;
+        serde_json::from_str(&text).map_err(|e| {
+            EtlError::ParseError(format!("Invalid JSON from {}: {}", request.url, e))
+        })
+    }
+
+    fn build_request(&self, record: &DataRecord) -> Result<EnrichRequest> {
+        Ok(EnrichRequest {
+            url: render_url(&self.config.url, record)?,
+            body: self
+                .config
+                .body
+                .as_ref()
+                .map(|body| render_value(body, record))
+                .transpose()?,
+        })
+    }
+}
+
+/// 取代 URL 模板中的 `{field}`，值依 RFC 3986 編碼，`/`、`?`、`&` 與空白不會改變網址結構
+fn render_url(template: &str, record: &DataRecord) -> Result<String> {
+    render_with(template, record, |value| uri_encode(value.as_bytes()))
+}
+
+/// 取代 `{field}` 佔位符，欄位不存在或為 null 時回報錯誤
+fn render_template(template: &str, record: &DataRecord) -> Result<String> {
+    render_with(template, record, |value| value.to_string())
+}
+
+fn render_with(template: &str, record: &DataRecord, encode: impl Fn(&str) -> String) -> Result<String> {
+    let mut missing = None;
+    let rendered = TEMPLATE_FIELD.replace_all(template, |caps: &regex::Captures| {
+        let field = caps[1].trim();
+        match record.fields.get(field) {
+            Some(serde_json::Value::String(s)) => encode(s),
+            Some(value) if !value.is_null() => encode(&value.to_string()),
+            _ => {
+                missing.get_or_insert_with(|| field.to_string());
+                String::new()
+            }
+        }
+    });
+
+    match missing {
+        Some(field) => Err(EtlError::TransformError(format!(
+            "Field '{}' required by template '{}' is missing",
+            field, template
+        ))),
+        None => Ok(rendered.into_owned()),
+    }
+}
+
+fn render_value(value: &serde_json::Value, record: &DataRecord) -> Result<serde_json::Value> {
+    match value {
+        serde_json::Value::String(s) => {
+            // 整個字串為單一佔位符時保留欄位原始型別
+            if let Some(caps) = TEMPLATE_FIELD.captures(s).filter(|c| c[0].len() == s.len()) {
+                if let Some(field) = record.fields.get(caps[1].trim()) {
+                    return Ok(field.clone());
+                }
+            }
+            render_template(s, record).map(serde_json::Value::String)
+        }
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|item| render_value(item, record))
+            .collect::<Result<Vec<_>>>()
+            .map(serde_json::Value::Array),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), render_value(v, record)?)))
+            .collect::<Result<serde_json::Map<_, _>>>()
+            .map(serde_json::Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{record, TestResponse, TestServer};
+
+    #[test]
+    fn url_values_are_percent_encoded() {
+        let record = record(serde_json::json!({ "sku": "a b/c?d&e", "id": 42, "lang": "zh-TW" }));
+
+        assert_eq!(
+            render_url("https://api.example.com/items/{sku}?id={id}&lang={lang}", &record).unwrap(),
+            "https://api.example.com/items/a%20b%2Fc%3Fd%26e?id=42&lang=zh-TW"
+        );
+        assert_eq!(
+            render_url("https://api.example.com/search?q={sku}", &record).unwrap(),
+            "https://api.example.com/search?q=a%20b%2Fc%3Fd%26e"
+        );
+        assert!(matches!(
+            render_url("https://api.example.com/items/{missing}", &record),
+            Err(EtlError::TransformError(_))
+        ));
+    }
+
+    #[test]
+    fn body_values_keep_types_and_are_not_encoded() {
+        let record = record(serde_json::json!({ "sku": "a b/c", "id": 42 }));
+        let body = serde_json::json!({ "id": "{id}", "label": "sku {sku}", "tags": ["{sku}"] });
+
+        assert_eq!(
+            render_value(&body, &record).unwrap(),
+            serde_json::json!({ "id": 42, "label": "sku a b/c", "tags": ["a b/c"] })
+        );
+    }
+
+    async fn enrich(server: &TestServer, on_error: &str) -> Result<Vec<DataRecord>> {
+        let config: EnrichConfig = serde_json::from_value(serde_json::json!({
+            "url": format!("{}/items/{{sku}}", server.url),
+            "fields": { "price": "$.price" },
+            "on_error": on_error,
+        }))
+        .unwrap();
+        let transformation = TransformationConfig {
+            name: "prices".to_string(),
+            source_field: "sku".to_string(),
+            target_field: None,
+            transformation: TransformationType::Enrich(Box::new(config.clone())),
+            condition: None,
+        };
+        let records = ["a/1", "missing", "a/1"]
+            .iter()
+            .map(|sku| record(serde_json::json!({ "sku": sku })))
+            .collect();
+        ApiEnricher::new("prices", &config, None).enrich(records, &transformation).await
+    }
+
+    async fn server() -> TestServer {
+        TestServer::start(|request| match request.path() {
+            "/items/a%2F1" => TestResponse::json(200, serde_json::json!({ "price": 10 })),
+            _ => TestResponse::new(404, "not found"),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn error_policies() {
+        let server = server().await;
+
+        assert!(enrich(&server, "fail").await.is_err());
+
+        let skipped = enrich(&server, "skip").await.unwrap();
+        assert_eq!(skipped.len(), 2);
+        assert!(skipped.iter().all(|r| r.fields["price"] == 10));
+
+        let nulls = enrich(&server, "null").await.unwrap();
+        assert_eq!(nulls.len(), 3);
+        assert_eq!(nulls[1].fields["price"], serde_json::Value::Null);
+
+        let kept = enrich(&server, "keep").await.unwrap();
+        assert_eq!(kept.len(), 3);
+        assert!(!kept[1].fields.contains_key("price"));
+        assert_eq!(kept[2].fields["price"], 10);
+    }
+
+    #[tokio::test]
+    async fn identical_requests_are_sent_once() {
+        let server = server().await;
+        enrich(&server, "keep").await.unwrap();
+
+        let paths: Vec<String> = server.requests().iter().map(|r| r.path().to_string()).collect();
+        assert_eq!(paths.len(), 2);
+        assert!(paths.contains(&"/items/a%2F1".to_string()));
+    }
+}
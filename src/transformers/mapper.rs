@@ -0,0 +1,655 @@
+use crate::utils::error::{EtlError, Result};
+use calamine::{open_workbook_auto, Data, Reader};
+use csv::ReaderBuilder;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// mapping 檔案：列出所有要載入的查找表，每個查找表使用獨立的命名空間
+///
+/// ```json
+/// {
+///   "lookups": [
+///     { "name": "category", "format": "inline", "values": { "1": "Electronics" } },
+///     { "name": "product", "format": "csv", "path": "products.csv",
+///       "key_column": "sku", "value_columns": ["name", "price"], "on_missing": "null" }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct MappingFile {
+    pub lookups: Vec<LookupDefinition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LookupDefinition {
+    pub name: String,
+    #[serde(flatten)]
+    pub source: LookupSource,
+    /// 作為查找鍵的欄位，CSV/Excel/JSON 陣列時必填
+    pub key_column: Option<String>,
+    /// 要回傳的欄位，未設定時回傳除鍵以外的所有欄位
+    pub value_columns: Option<Vec<String>>,
+    #[serde(default)]
+    pub on_missing: MissingLookupPolicy,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum LookupSource {
+    Inline {
+        values: HashMap<String, serde_json::Value>,
+    },
+    Json {
+        path: String,
+    },
+    Csv {
+        path: String,
+        delimiter: Option<char>,
+    },
+    Excel {
+        path: String,
+        sheet: Option<String>,
+    },
+}
+
+/// 找不到對應鍵時的處理方式
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingLookupPolicy {
+    /// 保留原始值
+    #[default]
+    Keep,
+    /// 輸出 null
+    Null,
+    /// 輸出指定的預設值
+    Default(serde_json::Value),
+    /// 回報 TransformError
+    Fail,
+}
+
+type LookupRow = HashMap<String, serde_json::Value>;
+
+#[derive(Debug, Clone)]
+pub struct LookupTable {
+    rows: HashMap<String, LookupRow>,
+    value_columns: Vec<String>,
+    on_missing: MissingLookupPolicy,
+}
+
+const SINGLE_VALUE_COLUMN: &str = "value";
+
+impl LookupTable {
+    /// 建立單值查找表（鍵 -> 值）
+    pub fn from_pairs(pairs: Vec<(String, serde_json::Value)>, on_missing: MissingLookupPolicy) -> Self {
+        let rows = pairs
+            .into_iter()
+            .map(|(key, value)| (key, HashMap::from([(SINGLE_VALUE_COLUMN.to_string(), value)])))
+            .collect();
+
+        Self {
+            rows,
+            value_columns: vec![SINGLE_VALUE_COLUMN.to_string()],
+            on_missing,
+        }
+    }
+
+    pub fn value_columns(&self) -> &[String] {
+        &self.value_columns
+    }
+
+    pub fn on_missing(&self) -> &MissingLookupPolicy {
+        &self.on_missing
+    }
+
+    pub fn is_multi_column(&self) -> bool {
+        self.value_columns.len() > 1
+    }
+
+    /// 取得鍵對應的欄位，順序與 `value_columns` 相同
+    pub fn get(&self, key: &str) -> Option<Vec<(&str, serde_json::Value)>> {
+        self.rows.get(key).map(|row| {
+            self.value_columns
+                .iter()
+                .map(|column| {
+                    let value = row.get(column).cloned().unwrap_or(serde_json::Value::Null);
+                    (column.as_str(), value)
+                })
+                .collect()
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+/// 將 JSON 值轉為查找鍵，數字與布林值以字串形式比對
+pub fn lookup_key(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+pub struct MappingLoader;
+
+impl MappingLoader {
+    /// 載入 mapping 檔案，表格路徑以 mapping 檔案所在目錄為基準
+    pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Vec<(String, LookupTable)>> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            EtlError::ConfigError(format!("Failed to read mapping file {}: {}", path.display(), e))
+        })?;
+        let mapping: MappingFile = serde_json::from_str(&content)?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        mapping
+            .lookups
+            .into_iter()
+            .map(|definition| {
+                let table = Self::load_table(&definition, base_dir)?;
+                Ok((definition.name, table))
+            })
+            .collect()
+    }
+
+    pub fn load_table(definition: &LookupDefinition, base_dir: &Path) -> Result<LookupTable> {
+        let (columns, rows) = match &definition.source {
+            LookupSource::Inline { values } => {
+                let pairs = values.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                return Self::from_key_value_map(definition, pairs);
+            }
+            LookupSource::Json { path } => {
+                let path = Self::resolve(base_dir, path);
+                let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+                match json {
+                    serde_json::Value::Object(map) => {
+                        return Self::from_key_value_map(definition, map.into_iter().collect());
+                    }
+                    serde_json::Value::Array(items) => Self::rows_from_json_array(items)?,
+                    _ => {
+                        return Err(EtlError::ParseError(format!(
+                            "Lookup file {} must contain a JSON object or array",
+                            path.display()
+                        )))
+                    }
+                }
+            }
+            LookupSource::Csv { path, delimiter } => {
+                Self::rows_from_csv(&Self::resolve(base_dir, path), delimiter.unwrap_or(','))?
+            }
+            LookupSource::Excel { path, sheet } => {
+                Self::rows_from_excel(&Self::resolve(base_dir, path), sheet.as_deref())?
+            }
+        };
+
+        Self::build_table(definition, columns, rows)
+    }
+
+    fn from_key_value_map(
+        definition: &LookupDefinition,
+        pairs: Vec<(String, serde_json::Value)>,
+    ) -> Result<LookupTable> {
+        // 值為物件時視為多欄位查找表
+        if pairs.iter().any(|(_, v)| v.is_object()) {
+            let mut columns = Vec::new();
+            let rows = pairs
+                .into_iter()
+                .map(|(key, value)| {
+                    let row: LookupRow = match value {
+                        serde_json::Value::Object(map) => map.into_iter().collect(),
+                        other => HashMap::from([(SINGLE_VALUE_COLUMN.to_string(), other)]),
+                    };
+                    for column in row.keys() {
+                        if !columns.contains(column) {
+                            columns.push(column.clone());
+                        }
+                    }
+                    (key, row)
+                })
+                .collect();
+            columns.sort();
+            let value_columns = definition.value_columns.clone().unwrap_or(columns);
+            return Ok(LookupTable {
+                rows,
+                value_columns,
+                on_missing: definition.on_missing.clone(),
+            });
+        }
+
+        Ok(LookupTable::from_pairs(pairs, definition.on_missing.clone()))
+    }
+
+    fn build_table(
+        definition: &LookupDefinition,
+        columns: Vec<String>,
+        rows: Vec<LookupRow>,
+    ) -> Result<LookupTable> {
+        let key_column = definition.key_column.as_ref().ok_or_else(|| {
+            EtlError::ConfigError(format!("Lookup '{}' requires key_column", definition.name))
+        })?;
+        if !columns.contains(key_column) {
+            return Err(EtlError::ConfigError(format!(
+                "Lookup '{}' has no key column '{}'",
+                definition.name, key_column
+            )));
+        }
+
+        let value_columns = match &definition.value_columns {
+            Some(selected) => {
+                if let Some(missing) = selected.iter().find(|c| !columns.contains(c)) {
+                    return Err(EtlError::ConfigError(format!(
+                        "Lookup '{}' has no value column '{}'",
+                        definition.name, missing
+                    )));
+                }
+                selected.clone()
+            }
+            None => columns.into_iter().filter(|c| c != key_column).collect(),
+        };
+
+        let rows = rows
+            .into_iter()
+            .filter_map(|row| {
+                let key = row.get(key_column).map(lookup_key)?;
+                Some((key, row))
+            })
+            .collect();
+
+        Ok(LookupTable {
+            rows,
+            value_columns,
+            on_missing: definition.on_missing.clone(),
+        })
+    }
+
+    fn rows_from_json_array(
+        items: Vec<serde_json::Value>,
+    ) -> Result<(Vec<String>, Vec<LookupRow>)> {
+        let mut columns: Vec<String> = Vec::new();
+        let mut rows = Vec::new();
+
+        for item in items {
+            let serde_json::Value::Object(map) = item else {
+                return Err(EtlError::ParseError(
+                    "Lookup JSON arrays must contain objects".to_string(),
+                ));
+            };
+            for key in map.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+            rows.push(map.into_iter().collect());
+        }
+
+        Ok((columns, rows))
+    }
+
+    fn rows_from_csv(
+        path: &Path,
+        delimiter: char,
+    ) -> Result<(Vec<String>, Vec<LookupRow>)> {
+        let mut reader = ReaderBuilder::new()
+            .delimiter(delimiter as u8)
+            .from_path(path)?;
+        let columns: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+
+        let mut rows = Vec::new();
+        for result in reader.records() {
+            let record = result?;
+            let row = columns
+                .iter()
+                .zip(record.iter())
+                .map(|(column, field)| (column.clone(), serde_json::Value::String(field.to_string())))
+                .collect();
+            rows.push(row);
+        }
+
+        Ok((columns, rows))
+    }
+
+    fn rows_from_excel(
+        path: &Path,
+        sheet: Option<&str>,
+    ) -> Result<(Vec<String>, Vec<LookupRow>)> {
+        let excel_error = |e: calamine::Error| {
+            EtlError::ParseError(format!("Failed to read Excel file {}: {}", path.display(), e))
+        };
+        let mut workbook = open_workbook_auto(path).map_err(excel_error)?;
+        let range = match sheet {
+            Some(name) => workbook.worksheet_range(name).map_err(excel_error)?,
+            None => workbook
+                .worksheet_range_at(0)
+                .ok_or_else(|| {
+                    EtlError::ParseError(format!("Excel file {} has no sheets", path.display()))
+                })?
+                .map_err(excel_error)?,
+        };
+
+        let mut sheet_rows = range.rows();
+        let columns: Vec<String> = match sheet_rows.next() {
+            Some(header) => header.iter().map(|cell| cell.to_string()).collect(),
+            None => return Ok((Vec::new(), Vec::new())),
+        };
+
+        let rows = sheet_rows
+            .map(|cells| {
+                columns
+                    .iter()
+                    .zip(cells.iter())
+                    .map(|(column, cell)| (column.clone(), Self::excel_cell_to_json(cell)))
+                    .collect()
+            })
+            .collect();
+
+        Ok((columns, rows))
+    }
+
+    fn excel_cell_to_json(cell: &Data) -> serde_json::Value {
+        match cell {
+            Data::Int(i) => serde_json::Value::Number((*i).into()),
+            // Excel 數字一律為浮點數，整數值轉回整數以便作為鍵比對
+            Data::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => {
+                serde_json::Value::Number((*f as i64).into())
+            }
+            Data::Float(f) => serde_json::Number::from_f64(*f)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            Data::Bool(b) => serde_json::Value::Bool(*b),
+            Data::Empty | Data::Error(_) => serde_json::Value::Null,
+            other => serde_json::Value::String(other.to_string()),
+        }
+    }
+
+    fn resolve(base_dir: &Path, path: &str) -> PathBuf {
+        let path = Path::new(path);
+        if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            base_dir.join(path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::TransformationConfig;
+    use crate::models::data_types::DataRecord;
+    use crate::test_support::records;
+    use crate::transformers::processor::DataProcessor;
+    use serde_json::json;
+    use std::io::Write;
+
+    /// 以 inline string 與數字儲存格產生最小的 xlsx 檔案
+    fn write_xlsx(path: &Path, sheet: &str, rows: &[Vec<serde_json::Value>]) {
+        let cells: String = rows
+            .iter()
+            .enumerate()
+            .map(|(r, row)| {
+                let cells: String = row
+                    .iter()
+                    .enumerate()
+                    .map(|(c, value)| {
+                        let reference = format!("{}{}", (b'A' + c as u8) as char, r + 1);
+                        match value {
+                            serde_json::Value::String(s) => {
+                                format!(r#"<c r="{}" t="inlineStr"><is><t>{}</t></is></c>"#, reference, s)
+                            }
+                            other => format!(r#"<c r="{}"><v>{}</v></c>"#, reference, other),
+                        }
+                    })
+                    .collect();
+                format!(r#"<row r="{}">{}</row>"#, r + 1, cells)
+            })
+            .collect();
+
+        let files = [
+            (
+                "[Content_Types].xml",
+                r#"<?xml version="1.0" encoding="UTF-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/></Types>"#.to_string(),
+            ),
+            (
+                "_rels/.rels",
+                r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#.to_string(),
+            ),
+            (
+                "xl/workbook.xml",
+                format!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#,
+                    sheet
+                ),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/></Relationships>"#.to_string(),
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                format!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>{}</sheetData></worksheet>"#,
+                    cells
+                ),
+            ),
+        ];
+
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+        for (name, content) in files {
+            zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    /// 將 mapping 檔案寫入 `dir` 並載入所有查找表
+    fn processor_with(dir: &Path, lookups: serde_json::Value) -> Result<DataProcessor> {
+        let mapping_file = dir.join("mapping.json");
+        std::fs::write(&mapping_file, json!({ "lookups": lookups }).to_string()).unwrap();
+        let processor = DataProcessor::new();
+        for (name, table) in MappingLoader::load_file(&mapping_file)? {
+            processor.load_mapping(name, table);
+        }
+        Ok(processor)
+    }
+
+    fn lookup(source_field: &str, target_field: &str, table: &str) -> TransformationConfig {
+        serde_json::from_value(json!({
+            "name": target_field,
+            "source_field": source_field,
+            "target_field": target_field,
+            "transformation": { "type": "lookup", "table": table }
+        }))
+        .unwrap()
+    }
+
+    fn fields(records: Vec<DataRecord>) -> Vec<serde_json::Value> {
+        records
+            .into_iter()
+            .map(|record| serde_json::Value::Object(record.fields.into_iter().collect()))
+            .collect()
+    }
+
+    fn config_error<T>(result: Result<T>) -> String {
+        match result {
+            Err(EtlError::ConfigError(message)) => message,
+            Err(other) => panic!("expected ConfigError, got {:?}", other),
+            Ok(_) => panic!("expected ConfigError, got Ok"),
+        }
+    }
+
+    #[test]
+    fn csv_lookup_with_several_value_columns_writes_prefixed_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("products.csv"), "sku;name;price;stock\nA1;Keyboard;990;4\nB2;Mouse;490;0\n").unwrap();
+        let processor = processor_with(
+            dir.path(),
+            json!([{
+                "name": "product", "format": "csv", "path": "products.csv", "delimiter": ";",
+                "key_column": "sku", "value_columns": ["name", "price"]
+            }]),
+        )
+        .unwrap();
+
+        let output = processor
+            .process_transformations(records(json!([{ "sku": "B2" }])), &[lookup("sku", "product", "product")])
+            .unwrap();
+
+        assert_eq!(fields(output), vec![json!({ "product_name": "Mouse", "product_price": "490" })]);
+    }
+
+    #[test]
+    fn value_columns_default_to_every_column_except_the_key() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("products.csv"), "sku,name,price\nA1,Keyboard,990\n").unwrap();
+        let definition: LookupDefinition = serde_json::from_value(json!({
+            "name": "product", "format": "csv", "path": "products.csv", "key_column": "sku"
+        }))
+        .unwrap();
+
+        let table = MappingLoader::load_table(&definition, dir.path()).unwrap();
+
+        assert_eq!(table.value_columns(), ["name", "price"]);
+        assert_eq!(table.get("A1").unwrap(), vec![("name", json!("Keyboard")), ("price", json!("990"))]);
+    }
+
+    #[test]
+    fn excel_lookup_matches_numeric_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        write_xlsx(
+            &dir.path().join("regions.xlsx"),
+            "Regions",
+            &[
+                vec![json!("code"), json!("label")],
+                vec![json!(1), json!("North")],
+                vec![json!(2), json!("South")],
+            ],
+        );
+        let processor = processor_with(
+            dir.path(),
+            json!([{
+                "name": "region", "format": "excel", "path": "regions.xlsx", "sheet": "Regions",
+                "key_column": "code"
+            }]),
+        )
+        .unwrap();
+
+        let output = processor
+            .process_transformations(
+                records(json!([{ "region_id": 2 }, { "region_id": "1" }])),
+                &[lookup("region_id", "region", "region")],
+            )
+            .unwrap();
+
+        assert_eq!(fields(output), vec![json!({ "region": "South" }), json!({ "region": "North" })]);
+
+        let missing_sheet = processor_with(
+            dir.path(),
+            json!([{ "name": "region", "format": "excel", "path": "regions.xlsx", "sheet": "Nope", "key_column": "code" }]),
+        );
+        assert!(matches!(missing_sheet, Err(EtlError::ParseError(_))));
+    }
+
+    #[test]
+    fn json_arrays_and_objects_are_both_lookup_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("users.json"),
+            json!([{ "id": 7, "name": "Ann", "team": "ops" }, { "id": 8, "name": "Bob", "team": "dev" }]).to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("teams.json"),
+            json!({ "ops": { "lead": "Ann", "floor": 3 }, "dev": { "lead": "Cid", "floor": 5 } }).to_string(),
+        )
+        .unwrap();
+        let processor = processor_with(
+            dir.path(),
+            json!([
+                { "name": "user", "format": "json", "path": "users.json", "key_column": "id", "value_columns": ["name"] },
+                { "name": "team", "format": "json", "path": "teams.json" }
+            ]),
+        )
+        .unwrap();
+
+        let output = processor
+            .process_transformations(
+                records(json!([{ "user_id": 8, "team": "ops" }])),
+                &[lookup("user_id", "user", "user"), lookup("team", "team", "team")],
+            )
+            .unwrap();
+
+        assert_eq!(fields(output), vec![json!({ "user": "Bob", "team_floor": 3, "team_lead": "Ann" })]);
+    }
+
+    #[test]
+    fn key_and_value_columns_are_validated() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("products.csv"), "sku,name\nA1,Keyboard\n").unwrap();
+        let csv = |extra: serde_json::Value| {
+            let mut definition = json!({ "name": "product", "format": "csv", "path": "products.csv" });
+            definition.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+            processor_with(dir.path(), json!([definition]))
+        };
+
+        assert_eq!(config_error(csv(json!({}))), "Lookup 'product' requires key_column");
+        assert_eq!(config_error(csv(json!({ "key_column": "id" }))), "Lookup 'product' has no key column 'id'");
+        assert_eq!(
+            config_error(csv(json!({ "key_column": "sku", "value_columns": ["name", "price"] }))),
+            "Lookup 'product' has no value column 'price'"
+        );
+
+        std::fs::write(dir.path().join("list.json"), "[1, 2]").unwrap();
+        let not_objects = processor_with(
+            dir.path(),
+            json!([{ "name": "list", "format": "json", "path": "list.json", "key_column": "id" }]),
+        );
+        assert!(matches!(not_objects, Err(EtlError::ParseError(_))));
+    }
+
+    #[test]
+    fn on_missing_policies() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("products.csv"), "sku,name,price\nA1,Keyboard,990\n").unwrap();
+        let table = |on_missing: serde_json::Value| {
+            json!({
+                "name": format!("product_{}", on_missing.as_str().unwrap_or("default")),
+                "format": "csv", "path": "products.csv", "key_column": "sku", "on_missing": on_missing
+            })
+        };
+        let processor = processor_with(
+            dir.path(),
+            json!([
+                { "name": "category", "format": "inline", "values": { "1": "Books" } },
+                table(json!("null")),
+                table(json!({ "default": "n/a" })),
+                table(json!("fail"))
+            ]),
+        )
+        .unwrap();
+        let input = || records(json!([{ "sku": "Z9", "category_id": 5 }]));
+        let run = |transformation| processor.process_transformations(input(), &[transformation]).map(fields);
+
+        assert_eq!(run(lookup("category_id", "category", "category")).unwrap(), vec![json!({ "category": 5 })]);
+        assert_eq!(
+            run(lookup("sku", "product", "product_null")).unwrap(),
+            vec![json!({ "product_name": null, "product_price": null })]
+        );
+        assert_eq!(
+            run(lookup("sku", "product", "product_default")).unwrap(),
+            vec![json!({ "product_name": "n/a", "product_price": "n/a" })]
+        );
+        match run(lookup("sku", "product", "product_fail")) {
+            Err(EtlError::TransformError(message)) => {
+                assert_eq!(message, "Key 'Z9' not found in lookup table 'product_fail'")
+            }
+            other => panic!("expected TransformError, got {:?}", other),
+        }
+    }
+}
@@ -1,2 +1,3 @@
 pub mod processor;
 pub mod mapper;
+pub mod enricher;
@@ -1,10 +1,22 @@
-use crate::models::data_types::{DataRecord, MappingRule, TransformationType};
-use crate::utils::error::Result;
+use crate::config::settings::{
+    ComparisonOperator, ConditionConfig, DataType, TransformationConfig, TransformationType,
+};
+use crate::models::data_types::{DataRecord, MappingRule};
+use crate::transformers::mapper::{lookup_key, LookupTable, MissingLookupPolicy};
+use crate::utils::error::{EtlError, Result};
 use dashmap::DashMap;
 use rayon::prelude::*;
+use regex::Regex;
+use std::sync::LazyLock;
 
 pub struct DataProcessor {
-    mapping_cache: DashMap<String, String>,
+    mapping_cache: DashMap<String, LookupTable>,
+}
+
+impl Default for DataProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl DataProcessor {
@@ -14,10 +26,9 @@ impl DataProcessor {
         }
     }
 
-    pub fn load_mapping(&self, mapping_data: Vec<(String, String)>) {
-        for (key, value) in mapping_data {
-            self.mapping_cache.insert(key, value);
-        }
+    /// 載入查找表到指定命名空間，同名的表會被取代
+    pub fn load_mapping(&self, name: String, table: LookupTable) {
+        self.mapping_cache.insert(name, table);
     }
 
     pub fn process_records(
@@ -25,66 +36,384 @@ impl DataProcessor {
         records: Vec<DataRecord>,
         rules: &[MappingRule],
     ) -> Result<Vec<DataRecord>> {
-        let processed: Vec<DataRecord> = records
+        let transformations: Vec<TransformationConfig> =
+            rules.iter().cloned().map(TransformationConfig::from).collect();
+        self.process_transformations(records, &transformations)
+    }
+
+    /// 依序套用轉換，輸出記錄只包含各轉換的目標欄位；被 `filter` 排除的記錄會被移除
+    pub fn process_transformations(
+        &self,
+        records: Vec<DataRecord>,
+        transformations: &[TransformationConfig],
+    ) -> Result<Vec<DataRecord>> {
+        let processed: Vec<Option<DataRecord>> = records
             .par_iter()
-            .map(|record| self.apply_rules(record, rules))
+            .map(|record| self.apply_transformations(record, transformations))
             .collect::<Result<Vec<_>>>()?;
 
-        Ok(processed)
+        Ok(processed.into_iter().flatten().collect())
     }
 
-    fn apply_rules(&self, record: &DataRecord, rules: &[MappingRule]) -> Result<DataRecord> {
+    fn apply_transformations(
+        &self,
+        record: &DataRecord,
+        transformations: &[TransformationConfig],
+    ) -> Result<Option<DataRecord>> {
         let mut new_record = DataRecord {
             fields: std::collections::HashMap::new(),
         };
 
-        for rule in rules {
-            if let Some(value) = record.fields.get(&rule.source_field) {
-                let transformed_value = match &rule.transformation {
-                    Some(TransformationType::Uppercase) => {
-                        serde_json::Value::String(
-                            value.as_str()
-                                .unwrap_or("")
-                                .to_uppercase()
-                        )
+        for transformation in transformations {
+            if let Some(condition) = &transformation.condition {
+                if !evaluate_condition(record, condition)? {
+                    continue;
+                }
+            }
+
+            let target_field = transformation
+                .target_field
+                .as_deref()
+                .unwrap_or(&transformation.source_field);
+
+            // 不依賴來源欄位值的轉換
+            match &transformation.transformation {
+                TransformationType::Filter { condition } => {
+                    if !evaluate_condition(record, &parse_filter(condition)?)? {
+                        return Ok(None);
+                    }
+                    continue;
+                }
+                TransformationType::Format { template } => {
+                    let formatted = format_template(template, record);
+                    new_record.fields.insert(target_field.to_string(), formatted);
+                    continue;
+                }
+                // 欄位已由 `ApiEnricher` 寫入記錄，這裡只負責投影
+                TransformationType::Enrich(enrich) => {
+                    for field in enrich.fields.keys() {
+                        if let Some(value) = record.fields.get(field) {
+                            new_record.fields.insert(field.clone(), value.clone());
+                        }
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            if let Some(value) = record.fields.get(&transformation.source_field) {
+                let transformed_value = match &transformation.transformation {
+                    TransformationType::Copy => value.clone(),
+                    TransformationType::Uppercase => {
+                        serde_json::Value::String(value_to_string(value).to_uppercase())
+                    }
+                    TransformationType::Lowercase => {
+                        serde_json::Value::String(value_to_string(value).to_lowercase())
+                    }
+                    TransformationType::Calculate { expression } => {
+                        self.calculate_field(value, expression, &transformation.name)?
                     }
-                    Some(TransformationType::Lowercase) => {
-                        serde_json::Value::String(
-                            value.as_str()
-                                .unwrap_or("")
-                                .to_lowercase()
-                        )
+                    TransformationType::Lookup { table } => {
+                        for (field, looked_up) in self.lookup_value(value, table, target_field)? {
+                            new_record.fields.insert(field, looked_up);
+                        }
+                        continue;
                     }
-                    Some(TransformationType::Calculate(expr)) => {
-                        self.calculate_field(value, expr)?
+                    TransformationType::Map { mapping } => mapping
+                        .get(&lookup_key(value))
+                        .map(|mapped| serde_json::Value::String(mapped.clone()))
+                        .unwrap_or_else(|| value.clone()),
+                    TransformationType::Convert { to_type } => convert_value(value, to_type)?,
+                    TransformationType::Aggregate { .. }
+                    | TransformationType::Join { .. }
+                    | TransformationType::Custom { .. } => {
+                        return Err(EtlError::TransformError(format!(
+                            "Transformation '{}' is not supported as a per-record transformation",
+                            transformation.name
+                        )));
                     }
-                    Some(TransformationType::Lookup(mapping_name)) => {
-                        self.lookup_value(value, mapping_name)?
+                    TransformationType::Filter { .. }
+                    | TransformationType::Format { .. }
+                    | TransformationType::Enrich(_) => {
+                        unreachable!("handled above")
                     }
-                    None => value.clone(),
                 };
 
-                new_record.fields.insert(rule.target_field.clone(), transformed_value);
+                new_record.fields.insert(target_field.to_string(), transformed_value);
             }
         }
 
-        Ok(new_record)
+        Ok(Some(new_record))
     }
 
-    fn calculate_field(&self, value: &serde_json::Value, expr: &str) -> Result<serde_json::Value> {
-        // 實作簡單計算邏輯
-        // 可以整合 evalexpr 套件來處理複雜運算式
-        Ok(value.clone())
+    /// 尚未支援任何運算式，回報設定錯誤而不是原值輸出，避免寫出看似已計算的錯誤資料
+    fn calculate_field(&self, _value: &serde_json::Value, expression: &str, name: &str) -> Result<serde_json::Value> {
+        Err(EtlError::ConfigError(format!(
+            "Unsupported calculate expression '{}' in transformation '{}'",
+            expression, name
+        )))
     }
 
-    fn lookup_value(&self, value: &serde_json::Value, mapping_name: &str) -> Result<serde_json::Value> {
-        let key = value.as_str().unwrap_or("");
-        let lookup_key = format!("{}:{}", mapping_name, key);
+    /// 回傳要寫入的欄位；多欄位查找表的欄位命名為 `{target_field}_{column}`
+    fn lookup_value(
+        &self,
+        value: &serde_json::Value,
+        mapping_name: &str,
+        target_field: &str,
+    ) -> Result<Vec<(String, serde_json::Value)>> {
+        let table = self.mapping_cache.get(mapping_name).ok_or_else(|| {
+            EtlError::TransformError(format!("Lookup table '{}' is not loaded", mapping_name))
+        })?;
+        let key = lookup_key(value);
 
-        if let Some(mapped_value) = self.mapping_cache.get(&lookup_key) {
-            Ok(serde_json::Value::String(mapped_value.clone()))
-        } else {
-            Ok(value.clone())
+        let field_name = |column: &str| {
+            if table.is_multi_column() {
+                format!("{}_{}", target_field, column)
+            } else {
+                target_field.to_string()
+            }
+        };
+
+        if let Some(columns) = table.get(&key) {
+            return Ok(columns
+                .into_iter()
+                .map(|(column, mapped)| (field_name(column), mapped))
+                .collect());
         }
+
+        let fallback = match table.on_missing() {
+            MissingLookupPolicy::Keep => return Ok(vec![(target_field.to_string(), value.clone())]),
+            MissingLookupPolicy::Null => serde_json::Value::Null,
+            MissingLookupPolicy::Default(default) => default.clone(),
+            MissingLookupPolicy::Fail => {
+                return Err(EtlError::TransformError(format!(
+                    "Key '{}' not found in lookup table '{}'",
+                    key, mapping_name
+                )));
+            }
+        };
+
+        Ok(table
+            .value_columns()
+            .iter()
+            .map(|column| (field_name(column), fallback.clone()))
+            .collect())
+    }
+}
+
+fn value_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn value_to_f64(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+pub(crate) static TEMPLATE_FIELD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{([^{}:]+)(?::[^{}]*)?\}").expect("valid template regex"));
+
+/// 以記錄欄位取代 `{field}` 佔位符，格式說明（`{field:...}`）目前會被忽略
+fn format_template(template: &str, record: &DataRecord) -> serde_json::Value {
+    let formatted = TEMPLATE_FIELD.replace_all(template, |caps: &regex::Captures| {
+        record
+            .fields
+            .get(caps[1].trim())
+            .map(value_to_string)
+            .unwrap_or_default()
+    });
+    serde_json::Value::String(formatted.into_owned())
+}
+
+fn convert_value(value: &serde_json::Value, to_type: &DataType) -> Result<serde_json::Value> {
+    if value.is_null() {
+        return Ok(serde_json::Value::Null);
+    }
+
+    let text = value_to_string(value);
+    let invalid = || {
+        EtlError::TransformError(format!("Cannot convert '{}' to {:?}", text, to_type))
+    };
+
+    let converted = match to_type {
+        DataType::String => serde_json::Value::String(text.clone()),
+        DataType::Integer => {
+            let trimmed = text.trim();
+            let int = match trimmed.parse::<i64>() {
+                Ok(int) => int,
+                Err(_) => match trimmed.parse::<f64>() {
+                    Ok(float) if float.fract() == 0.0 => float as i64,
+                    _ => return Err(invalid()),
+                },
+            };
+            serde_json::Value::Number(int.into())
+        }
+        DataType::Float => value_to_f64(value)
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            .ok_or_else(invalid)?,
+        DataType::Boolean => match value {
+            serde_json::Value::Bool(b) => serde_json::Value::Bool(*b),
+            _ => match text.trim().to_lowercase().as_str() {
+                "true" | "yes" | "1" => serde_json::Value::Bool(true),
+                "false" | "no" | "0" => serde_json::Value::Bool(false),
+                _ => return Err(invalid()),
+            },
+        },
+        DataType::Date => {
+            let date = chrono::NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+                .or_else(|_| {
+                    chrono::DateTime::parse_from_rfc3339(text.trim()).map(|dt| dt.date_naive())
+                })
+                .map_err(|_| invalid())?;
+            serde_json::Value::String(date.format("%Y-%m-%d").to_string())
+        }
+        DataType::DateTime => {
+            let datetime = chrono::DateTime::parse_from_rfc3339(text.trim())
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .or_else(|_| {
+                    chrono::NaiveDateTime::parse_from_str(text.trim(), "%Y-%m-%d %H:%M:%S")
+                        .map(|dt| dt.and_utc())
+                })
+                .map_err(|_| invalid())?;
+            serde_json::Value::String(datetime.to_rfc3339())
+        }
+        DataType::Json => match value {
+            serde_json::Value::String(s) => serde_json::from_str(s).map_err(|_| invalid())?,
+            other => other.clone(),
+        },
+    };
+
+    Ok(converted)
+}
+
+static FILTER_EXPRESSION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*([A-Za-z_][\w.]*)\s*(==|!=|>=|<=|>|<)\s*(.+?)\s*$")
+        .expect("valid filter regex")
+});
+
+/// 解析 `field op value` 形式的過濾條件，例如 `status == 'active'`、`revenue > 1000`
+fn parse_filter(condition: &str) -> Result<ConditionConfig> {
+    let caps = FILTER_EXPRESSION.captures(condition).ok_or_else(|| {
+        EtlError::TransformError(format!("Unsupported filter condition '{}'", condition))
+    })?;
+
+    let operator = match &caps[2] {
+        "==" => ComparisonOperator::Equal,
+        "!=" => ComparisonOperator::NotEqual,
+        ">=" => ComparisonOperator::GreaterEqual,
+        "<=" => ComparisonOperator::LessEqual,
+        ">" => ComparisonOperator::GreaterThan,
+        _ => ComparisonOperator::LessThan,
+    };
+
+    let literal = &caps[3];
+    let value = if literal.len() >= 2
+        && ((literal.starts_with('\'') && literal.ends_with('\''))
+            || (literal.starts_with('"') && literal.ends_with('"')))
+    {
+        serde_json::Value::String(literal[1..literal.len() - 1].to_string())
+    } else {
+        serde_json::from_str(literal).unwrap_or_else(|_| serde_json::Value::String(literal.to_string()))
+    };
+
+    Ok(ConditionConfig {
+        field: caps[1].to_string(),
+        operator,
+        value,
+    })
+}
+
+pub(crate) fn evaluate_condition(record: &DataRecord, condition: &ConditionConfig) -> Result<bool> {
+    let actual = record
+        .fields
+        .get(&condition.field)
+        .unwrap_or(&serde_json::Value::Null);
+    let expected = &condition.value;
+
+    let result = match condition.operator {
+        ComparisonOperator::Equal => values_equal(actual, expected),
+        ComparisonOperator::NotEqual => !values_equal(actual, expected),
+        ComparisonOperator::GreaterThan => compare_values(actual, expected).is_some_and(|o| o.is_gt()),
+        ComparisonOperator::LessThan => compare_values(actual, expected).is_some_and(|o| o.is_lt()),
+        ComparisonOperator::GreaterEqual => compare_values(actual, expected).is_some_and(|o| o.is_ge()),
+        ComparisonOperator::LessEqual => compare_values(actual, expected).is_some_and(|o| o.is_le()),
+        ComparisonOperator::Contains => match actual {
+            serde_json::Value::Array(items) => items.iter().any(|item| values_equal(item, expected)),
+            _ => value_to_string(actual).contains(&value_to_string(expected)),
+        },
+        ComparisonOperator::StartsWith => {
+            value_to_string(actual).starts_with(&value_to_string(expected))
+        }
+        ComparisonOperator::EndsWith => value_to_string(actual).ends_with(&value_to_string(expected)),
+        ComparisonOperator::Regex => {
+            let pattern = value_to_string(expected);
+            let regex = Regex::new(&pattern).map_err(|e| {
+                EtlError::TransformError(format!("Invalid regex '{}': {}", pattern, e))
+            })?;
+            regex.is_match(&value_to_string(actual))
+        }
+        ComparisonOperator::In | ComparisonOperator::NotIn => {
+            let candidates = expected.as_array().ok_or_else(|| {
+                EtlError::TransformError(format!(
+                    "Condition on '{}' requires an array value",
+                    condition.field
+                ))
+            })?;
+            let found = candidates.iter().any(|candidate| values_equal(actual, candidate));
+            matches!(condition.operator, ComparisonOperator::In) == found
+        }
+    };
+
+    Ok(result)
+}
+
+fn values_equal(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    if a == b {
+        return true;
+    }
+    match (value_to_f64(a), value_to_f64(b)) {
+        (Some(x), Some(y)) => x == y,
+        _ => !a.is_null() && !b.is_null() && value_to_string(a) == value_to_string(b),
+    }
+}
+
+fn compare_values(a: &serde_json::Value, b: &serde_json::Value) -> Option<std::cmp::Ordering> {
+    if a.is_null() || b.is_null() {
+        return None;
+    }
+    match (value_to_f64(a), value_to_f64(b)) {
+        (Some(x), Some(y)) => x.partial_cmp(&y),
+        _ => Some(value_to_string(a).cmp(&value_to_string(b))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::record;
+
+    #[test]
+    fn calculate_is_rejected_instead_of_passed_through() {
+        let transformation = TransformationConfig {
+            name: "discounted".to_string(),
+            source_field: "price".to_string(),
+            target_field: Some("final_price".to_string()),
+            transformation: TransformationType::Calculate { expression: "price * 0.9".to_string() },
+            condition: None,
+        };
+        let record = record(serde_json::json!({ "price": 100 }));
+
+        let error = DataProcessor::new().process_transformations(vec![record], &[transformation]).unwrap_err();
+
+        assert!(matches!(error, EtlError::ConfigError(_)));
+        assert!(error.to_string().contains("price * 0.9"));
     }
 }
@@ -1,2 +1,3 @@
 pub mod error;
-pub mod helpers;
\ No newline at end of file
+pub mod helpers;
+pub mod template;
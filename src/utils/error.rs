@@ -25,7 +25,7 @@ pub enum EtlError {
     JsonError(#[from] serde_json::Error),
 
     #[error("Zip archive error: {0}")]
-    ZipError(#[from] zip::result::ZipError),
+    ZipError(zip::result::ZipError),
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
@@ -41,18 +41,53 @@ pub enum EtlError {
 
     #[error("Validation error: {0}")]
     ValidationError(String),
+
+    /// 不是失敗：資料來源自上次執行後沒有變更（`on_not_modified: skip`）
+    #[error("No changes: {0}")]
+    NotModified(String),
 }
 
 pub type Result<T> = std::result::Result<T, EtlError>;
 
 impl From<reqwest::Error> for EtlError {
     fn from(value: Error) -> Self {
-        EtlError::ApiError(value.to_string())
+        // reqwest 的訊息不含底層原因（TLS、逾時、代理伺服器等），一併列出方便排查
+        let mut message = value.to_string();
+        let mut source = std::error::Error::source(&value);
+        while let Some(cause) = source {
+            let cause_message = cause.to_string();
+            if !message.ends_with(&cause_message) {
+                message.push_str(": ");
+                message.push_str(&cause_message);
+            }
+            source = cause.source();
+        }
+        EtlError::ApiError(message)
+    }
+}
+
+impl From<zip::result::ZipError> for EtlError {
+    fn from(value: zip::result::ZipError) -> Self {
+        match value {
+            zip::result::ZipError::InvalidPassword => {
+                EtlError::AuthError("Invalid password for encrypted zip archive".to_string())
+            }
+            zip::result::ZipError::UnsupportedArchive(zip::result::ZipError::PASSWORD_REQUIRED) => {
+                EtlError::AuthError("Zip archive is encrypted but no password was configured".to_string())
+            }
+            other => EtlError::ZipError(other),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for EtlError {
+    fn from(value: rusqlite::Error) -> Self {
+        EtlError::DatabaseError(value.to_string())
     }
 }
 
-impl From<backoff::Error<reqwest::Error>> for EtlError {
-    fn from(value: backoff::Error<reqwest::Error>) -> Self {
-        EtlError::ApiError(value.to_string())
+impl From<surrealdb::Error> for EtlError {
+    fn from(value: surrealdb::Error) -> Self {
+        EtlError::DatabaseError(value.to_string())
     }
 }
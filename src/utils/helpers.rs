@@ -0,0 +1,275 @@
+use crate::config::settings::SecretRef;
+use crate::utils::error::{EtlError, Result};
+use std::collections::HashMap;
+
+/// 解析 `SecretRef` 取得實際的敏感值
+pub fn resolve_secret(secret: &SecretRef) -> Result<String> {
+    match secret {
+        SecretRef::Env(name) => std::env::var(name).map_err(|_| {
+            EtlError::ConfigError(format!("Secret environment variable '{}' is not set", name))
+        }),
+        SecretRef::File(path) => {
+            let content = std::fs::read_to_string(path).map_err(|e| {
+                EtlError::ConfigError(format!("Failed to read secret file '{}': {}", path, e))
+            })?;
+            Ok(content.trim_end_matches(['\r', '\n']).to_string())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PathSegment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+/// 解析 JSONPath 風格的路徑：`$.data.items`、`data.items[0]`、`$['meta']['total']`、`$.pages[*].rows`
+fn parse_json_path(path: &str) -> Result<Vec<PathSegment>> {
+    let invalid = || EtlError::ConfigError(format!("Invalid JSON path '{}'", path));
+    let mut segments = Vec::new();
+    let mut rest = path.trim();
+    rest = rest.strip_prefix('$').unwrap_or(rest);
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(invalid)?;
+            let inner = after[..end].trim();
+            let segment = if inner == "*" {
+                PathSegment::Wildcard
+            } else if let Ok(index) = inner.parse::<usize>() {
+                PathSegment::Index(index)
+            } else {
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                    .ok_or_else(invalid)?;
+                PathSegment::Key(quoted.to_string())
+            };
+            segments.push(segment);
+            rest = &after[end + 1..];
+        } else {
+            rest = rest.strip_prefix('.').unwrap_or(rest);
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            let key = &rest[..end];
+            if key.is_empty() {
+                return Err(invalid());
+            }
+            segments.push(match key {
+                "*" => PathSegment::Wildcard,
+                _ => match key.parse::<usize>() {
+                    Ok(index) => PathSegment::Index(index),
+                    Err(_) => PathSegment::Key(key.to_string()),
+                },
+            });
+            rest = &rest[end..];
+        }
+    }
+
+    Ok(segments)
+}
+
+/// 取得路徑符合的所有節點，萬用字元 `*` 會展開陣列元素或物件值
+pub fn select_nodes<'a>(value: &'a serde_json::Value, path: &str) -> Result<Vec<&'a serde_json::Value>> {
+    Ok(select_segments(value, &parse_json_path(path)?))
+}
+
+fn select_segments<'a>(value: &'a serde_json::Value, segments: &[PathSegment]) -> Vec<&'a serde_json::Value> {
+    let mut current = vec![value];
+
+    for segment in segments {
+        current = current
+            .into_iter()
+            .flat_map(|node| -> Vec<&serde_json::Value> {
+                match (segment, node) {
+                    (PathSegment::Key(key), serde_json::Value::Object(map)) => {
+                        map.get(key).into_iter().collect()
+                    }
+                    (PathSegment::Index(index), serde_json::Value::Array(items)) => {
+                        items.get(*index).into_iter().collect()
+                    }
+                    (PathSegment::Wildcard, serde_json::Value::Array(items)) => items.iter().collect(),
+                    (PathSegment::Wildcard, serde_json::Value::Object(map)) => map.values().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+
+    current
+}
+
+/// 路徑在第一個萬用字元之前的部分存在時，空結果代表集合本身為空（例如 `edges: []`），而非路徑錯誤
+fn is_empty_collection(value: &serde_json::Value, path: &str) -> Result<bool> {
+    let segments = parse_json_path(path)?;
+    Ok(match segments.iter().position(|s| *s == PathSegment::Wildcard) {
+        Some(position) => !select_segments(value, &segments[..position]).is_empty(),
+        None => false,
+    })
+}
+
+/// 取得路徑符合的第一個節點，路徑格式錯誤時視為找不到
+pub fn select_path<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    select_nodes(value, path).ok()?.into_iter().next()
+}
+
+/// 從回應內容取出記錄：`records_path` 指向的陣列會被展開，並將 `envelope_fields` 複製到每筆記錄
+pub fn extract_records(
+    body: serde_json::Value,
+    records_path: Option<&str>,
+    envelope_fields: Option<&HashMap<String, String>>,
+) -> Result<Vec<serde_json::Value>> {
+    let envelope = match envelope_fields {
+        Some(fields) => fields
+            .iter()
+            .map(|(target, path)| {
+                let value = select_nodes(&body, path)?.into_iter().next().cloned();
+                Ok((target.clone(), value.unwrap_or(serde_json::Value::Null)))
+            })
+            .collect::<Result<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+
+    let records = match records_path {
+        Some(path) => {
+            let nodes = select_nodes(&body, path)?;
+            if nodes.is_empty() && !is_empty_collection(&body, path)? {
+                return Err(EtlError::ParseError(format!(
+                    "records_path '{}' did not match any node",
+                    path
+                )));
+            }
+            nodes
+                .into_iter()
+                .flat_map(|node| match node {
+                    serde_json::Value::Array(items) => items.clone(),
+                    other => vec![other.clone()],
+                })
+                .collect()
+        }
+        None => match body {
+            serde_json::Value::Array(items) => items,
+            other => vec![other],
+        },
+    };
+
+    if envelope.is_empty() {
+        return Ok(records);
+    }
+
+    Ok(records
+        .into_iter()
+        .map(|record| {
+            let mut map = match record {
+                serde_json::Value::Object(map) => map,
+                other => serde_json::Map::from_iter([("value".to_string(), other)]),
+            };
+            for (target, value) in &envelope {
+                map.insert(target.clone(), value.clone());
+            }
+            serde_json::Value::Object(map)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn envelope(fields: &[(&str, &str)]) -> HashMap<String, String> {
+        fields.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn parse_json_path_supports_brackets_quotes_and_wildcards() {
+        use PathSegment::*;
+        let key = |k: &str| Key(k.to_string());
+
+        assert_eq!(parse_json_path("$.data.items").unwrap(), vec![key("data"), key("items")]);
+        assert_eq!(parse_json_path("data.items[0]").unwrap(), vec![key("data"), key("items"), Index(0)]);
+        assert_eq!(
+            parse_json_path("$['meta'][\"total count\"]").unwrap(),
+            vec![key("meta"), key("total count")]
+        );
+        assert_eq!(
+            parse_json_path("$.pages[*].rows.*").unwrap(),
+            vec![key("pages"), Wildcard, key("rows"), Wildcard]
+        );
+        assert_eq!(parse_json_path("$['a.b'][ 2 ]").unwrap(), vec![key("a.b"), Index(2)]);
+        assert_eq!(parse_json_path("$").unwrap(), Vec::new());
+
+        for invalid in ["$.data[", "$[meta]", "$..items", "$.items."] {
+            assert!(matches!(parse_json_path(invalid), Err(EtlError::ConfigError(_))), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn wildcards_expand_arrays_and_object_values() {
+        let body = json!({
+            "pages": [{ "rows": [1, 2] }, { "rows": [3] }],
+            "totals": { "a": 1, "b": 2 }
+        });
+
+        let rows: Vec<_> = select_nodes(&body, "$.pages[*].rows[*]").unwrap().into_iter().cloned().collect();
+        assert_eq!(rows, vec![json!(1), json!(2), json!(3)]);
+        assert_eq!(select_nodes(&body, "totals.*").unwrap().len(), 2);
+        assert_eq!(select_path(&body, "$.pages[1].rows[0]"), Some(&json!(3)));
+        assert_eq!(select_path(&body, "$.pages[5]"), None);
+    }
+
+    #[test]
+    fn extract_records_flattens_matched_arrays() {
+        let body = json!({ "data": { "edges": [{ "node": { "id": 1 } }, { "node": { "id": 2 } }] } });
+
+        let records = extract_records(body.clone(), Some("$.data.edges[*].node"), None).unwrap();
+        assert_eq!(records, vec![json!({ "id": 1 }), json!({ "id": 2 })]);
+
+        let records = extract_records(body, Some("data.edges"), None).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let records = extract_records(json!([{ "id": 1 }]), None, None).unwrap();
+        assert_eq!(records, vec![json!({ "id": 1 })]);
+    }
+
+    #[test]
+    fn empty_collection_before_a_wildcard_is_not_an_error() {
+        let body = json!({ "data": { "edges": [] } });
+        assert!(is_empty_collection(&body, "$.data.edges[*].node").unwrap());
+        assert!(extract_records(body, Some("$.data.edges[*].node"), None).unwrap().is_empty());
+
+        let missing = json!({ "data": {} });
+        assert!(!is_empty_collection(&missing, "$.data.edges[*].node").unwrap());
+        assert!(!is_empty_collection(&missing, "$.data.edges").unwrap());
+    }
+
+    #[test]
+    fn non_matching_records_path_is_a_parse_error() {
+        let body = json!({ "data": { "items": [] } });
+        for path in ["$.data.results", "$.missing[*].id"] {
+            match extract_records(body.clone(), Some(path), None) {
+                Err(EtlError::ParseError(message)) => {
+                    assert_eq!(message, format!("records_path '{}' did not match any node", path))
+                }
+                other => panic!("expected ParseError for {}, got {:?}", path, other),
+            }
+        }
+    }
+
+    #[test]
+    fn envelope_fields_are_copied_to_every_record() {
+        let body = json!({ "meta": { "page": 3 }, "items": [{ "id": 1 }, 7] });
+        let fields = envelope(&[("page", "$.meta.page"), ("missing", "$.meta.none")]);
+
+        let records = extract_records(body, Some("items"), Some(&fields)).unwrap();
+
+        assert_eq!(
+            records,
+            vec![
+                json!({ "id": 1, "page": 3, "missing": null }),
+                json!({ "value": 7, "page": 3, "missing": null }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use crate::utils::error::{EtlError, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// `${NAME}`、`${NAME-7d}`、`${NAME+1h:%Y%m%d}`
+static VARIABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)(?:([+-])(\d+)([smhdw]))?(?::([^}]*))?\}")
+        .expect("valid variable regex")
+});
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// 日期運算的基準，只有日期時預設輸出 `%Y-%m-%d`
+#[derive(Debug, Clone, Copy)]
+struct Moment {
+    datetime: DateTime<FixedOffset>,
+    date_only: bool,
+}
+
+/// 展開 `${VAR}` 與日期運算式
+///
+/// 變數依序從明確指定的變數、內建的 `NOW` / `TODAY` / `YESTERDAY`、環境變數取得。
+/// 加上位移（`-7d`、`+2h`，單位 s/m/h/d/w）或格式（`:%Y%m%d`）時，值會被視為日期或時間。
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    variables: HashMap<String, String>,
+    now: DateTime<FixedOffset>,
+}
+
+impl Default for TemplateContext {
+    fn default() -> Self {
+        Self::new(HashMap::new())
+    }
+}
+
+impl TemplateContext {
+    pub fn new(variables: HashMap<String, String>) -> Self {
+        Self {
+            variables,
+            now: Local::now().fixed_offset(),
+        }
+    }
+
+    pub fn render(&self, input: &str) -> Result<String> {
+        let mut error = None;
+        let rendered = VARIABLE.replace_all(input, |caps: &regex::Captures| {
+            match self.expand(caps) {
+                Ok(value) => value,
+                Err(e) => {
+                    error.get_or_insert(e);
+                    String::new()
+                }
+            }
+        });
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(rendered.into_owned()),
+        }
+    }
+
+    /// 展開 JSON 中所有字串值（不含鍵）
+    pub fn render_value(&self, value: &serde_json::Value) -> Result<serde_json::Value> {
+        Ok(match value {
+            serde_json::Value::String(s) => serde_json::Value::String(self.render(s)?),
+            serde_json::Value::Array(items) => serde_json::Value::Array(
+                items.iter().map(|item| self.render_value(item)).collect::<Result<_>>()?,
+            ),
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.iter()
+                    .map(|(k, v)| Ok((k.clone(), self.render_value(v)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            other => other.clone(),
+        })
+    }
+
+    pub fn render_map(&self, map: &HashMap<String, String>) -> Result<HashMap<String, String>> {
+        map.iter()
+            .map(|(k, v)| Ok((k.clone(), self.render(v)?)))
+            .collect()
+    }
+
+    fn expand(&self, caps: &regex::Captures) -> Result<String> {
+        let name = &caps[1];
+        let offset = caps.get(2).map(|sign| (sign.as_str(), &caps[3], &caps[4]));
+        let format = caps.get(5).map(|f| f.as_str());
+
+        if offset.is_none() && format.is_none() {
+            if let Some(value) = self.lookup(name) {
+                return Ok(value);
+            }
+            if let Some(moment) = self.builtin(name) {
+                return Self::format_moment(moment, None);
+            }
+            return Err(Self::undefined(name));
+        }
+
+        let mut moment = match self.lookup(name) {
+            Some(value) => Self::parse_moment(&value).ok_or_else(|| {
+                EtlError::ConfigError(format!(
+                    "Variable '{}' = '{}' is not a date or datetime",
+                    name, value
+                ))
+            })?,
+            None => self.builtin(name).ok_or_else(|| Self::undefined(name))?,
+        };
+
+        if let Some((sign, amount, unit)) = offset {
+            let amount: i64 = amount.parse().map_err(|_| {
+                EtlError::ConfigError(format!("Invalid date offset in '{}'", &caps[0]))
+            })?;
+            let delta = match unit {
+                "s" => Duration::seconds(amount),
+                "m" => Duration::minutes(amount),
+                "h" => Duration::hours(amount),
+                "d" => Duration::days(amount),
+                _ => Duration::weeks(amount),
+            };
+            moment.datetime = if sign == "-" { moment.datetime - delta } else { moment.datetime + delta };
+            // 以時、分、秒位移後不再只是日期
+            moment.date_only &= matches!(unit, "d" | "w");
+        }
+
+        Self::format_moment(moment, format)
+    }
+
+    /// 明確指定的變數優先，其次是環境變數（內建日期名稱除外）
+    fn lookup(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.variables.get(name) {
+            return Some(value.clone());
+        }
+        if self.builtin(name).is_some() {
+            return None;
+        }
+        std::env::var(name).ok()
+    }
+
+    fn builtin(&self, name: &str) -> Option<Moment> {
+        let days_back = match name {
+            "NOW" => return Some(Moment { datetime: self.now, date_only: false }),
+            "TODAY" => 0,
+            "YESTERDAY" => 1,
+            _ => return None,
+        };
+        let midnight = self
+            .now
+            .date_naive()
+            .and_hms_opt(0, 0, 0)?
+            .and_local_timezone(*self.now.offset())
+            .single()?;
+        Some(Moment {
+            datetime: midnight - Duration::days(days_back),
+            date_only: true,
+        })
+    }
+
+    fn parse_moment(value: &str) -> Option<Moment> {
+        let value = value.trim();
+        let local_offset = *Local::now().fixed_offset().offset();
+
+        if let Ok(date) = NaiveDate::parse_from_str(value, DATE_FORMAT) {
+            let datetime = date.and_hms_opt(0, 0, 0)?.and_local_timezone(local_offset).single()?;
+            return Some(Moment { datetime, date_only: true });
+        }
+        if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+            return Some(Moment { datetime, date_only: false });
+        }
+        ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+            .and_then(|naive| naive.and_local_timezone(local_offset).single())
+            .map(|datetime| Moment { datetime, date_only: false })
+    }
+
+    fn format_moment(moment: Moment, format: Option<&str>) -> Result<String> {
+        let format = format.unwrap_or(if moment.date_only { DATE_FORMAT } else { DATETIME_FORMAT });
+        // 無效的格式會讓 chrono 在輸出時 panic，先行檢查
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            return Err(EtlError::ConfigError(format!("Invalid date format '{}'", format)));
+        }
+        Ok(moment.datetime.format(format).to_string())
+    }
+
+    fn undefined(name: &str) -> EtlError {
+        EtlError::ConfigError(format!("Template variable '{}' is not defined", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 固定在 2024-03-10 15:30:45 +08:00
+    fn context(variables: &[(&str, &str)]) -> TemplateContext {
+        TemplateContext {
+            variables: variables.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            now: DateTime::parse_from_rfc3339("2024-03-10T15:30:45+08:00").unwrap(),
+        }
+    }
+
+    fn config_error(result: Result<String>) -> String {
+        match result {
+            Err(EtlError::ConfigError(message)) => message,
+            other => panic!("expected ConfigError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn builtin_dates_support_offsets_and_formats() {
+        let context = context(&[]);
+        let cases = [
+            ("${TODAY}", "2024-03-10"),
+            ("${TODAY-7d}", "2024-03-03"),
+            ("${TODAY+1w}", "2024-03-17"),
+            ("${YESTERDAY:%Y%m%d}", "20240309"),
+            ("${NOW}", "2024-03-10T15:30:45+08:00"),
+            ("${NOW-6h}", "2024-03-10T09:30:45+08:00"),
+            ("${NOW+15s:%H:%M:%S}", "15:31:00"),
+            ("from=${TODAY-1d}&to=${TODAY}", "from=2024-03-09&to=2024-03-10"),
+        ];
+
+        for (template, expected) in cases {
+            assert_eq!(context.render(template).unwrap(), expected, "{}", template);
+        }
+    }
+
+    #[test]
+    fn variables_are_parsed_as_dates_when_offset_or_formatted() {
+        let context = context(&[
+            ("LAST_RUN_DATE", "2024-02-28"),
+            ("LAST_RUN_AT", "2024-02-28T23:00:00+00:00"),
+            ("REGION", "tw"),
+        ]);
+
+        assert_eq!(context.render("${LAST_RUN_DATE}").unwrap(), "2024-02-28");
+        assert_eq!(context.render("${LAST_RUN_DATE:%Y%m%d}").unwrap(), "20240228");
+        assert_eq!(context.render("${LAST_RUN_DATE+1d}").unwrap(), "2024-02-29");
+        assert_eq!(context.render("${LAST_RUN_AT+2h}").unwrap(), "2024-02-29T01:00:00+00:00");
+        assert_eq!(context.render("${REGION}").unwrap(), "tw");
+
+        let message = config_error(context.render("${REGION-1d}"));
+        assert_eq!(message, "Variable 'REGION' = 'tw' is not a date or datetime");
+    }
+
+    #[test]
+    fn time_offsets_turn_dates_into_datetimes() {
+        let context = context(&[]);
+        assert_eq!(context.render("${TODAY+2h}").unwrap(), "2024-03-10T02:00:00+08:00");
+        assert_eq!(context.render("${TODAY-30m}").unwrap(), "2024-03-09T23:30:00+08:00");
+        assert_eq!(context.render("${TODAY-1s}").unwrap(), "2024-03-09T23:59:59+08:00");
+        assert_eq!(context.render("${TODAY+1d}").unwrap(), "2024-03-11");
+    }
+
+    #[test]
+    fn undefined_variables_are_config_errors() {
+        let context = context(&[]);
+        let message = config_error(context.render("${ETL_TEMPLATE_TEST_UNDEFINED}"));
+        assert_eq!(message, "Template variable 'ETL_TEMPLATE_TEST_UNDEFINED' is not defined");
+        config_error(context.render("${ETL_TEMPLATE_TEST_UNDEFINED-1d}"));
+
+        let rendered = context.render_value(&serde_json::json!({ "ok": "${TODAY}", "bad": ["${NOPE_UNDEFINED}"] }));
+        assert!(matches!(rendered, Err(EtlError::ConfigError(_))));
+    }
+
+    #[test]
+    fn environment_is_the_fallback_for_unknown_names() {
+        std::env::set_var("ETL_TEMPLATE_TEST_REGION", "jp");
+        assert_eq!(context(&[]).render("${ETL_TEMPLATE_TEST_REGION}").unwrap(), "jp");
+
+        let explicit = context(&[("ETL_TEMPLATE_TEST_REGION", "tw")]);
+        assert_eq!(explicit.render("${ETL_TEMPLATE_TEST_REGION}").unwrap(), "tw");
+    }
+
+    #[test]
+    fn invalid_date_formats_are_rejected() {
+        let context = context(&[("LAST_RUN_DATE", "2024-02-28")]);
+        for template in ["${TODAY:%Q}", "${LAST_RUN_DATE:%Y-%}"] {
+            let message = config_error(context.render(template));
+            assert!(message.starts_with("Invalid date format"), "{}", message);
+        }
+    }
+}